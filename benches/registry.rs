@@ -0,0 +1,162 @@
+//
+//  Registry lookups: their cost should not depend on the number of registered types,
+//  nor on the order in which those types were registered.
+//
+//  For each size, registers that many filler structs, each implementing the Filler trait,
+//  then measures the look-up of the first, middle and last of them.
+//
+//  The registries are built once per process, hence each size is measured by a process of
+//  its own: without a size argument the bench runs itself once per size.
+//
+use std::env;
+use std::hint::black_box;
+use std::marker::PhantomData;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use poly::{Registration, StructId, StructInfo, TraitId, TraitInfo};
+use poly::__private::{self, VTableRegistryIndices, VTableRegistryTables};
+
+const SIZES: [usize; 3] = [16, 1024, 65536];
+
+//  The size measured by this process, selecting the fillers to register.
+static SIZE: AtomicUsize = AtomicUsize::new(0);
+
+trait Filler {}
+
+impl<T> Filler for T {}
+
+//  Only the ids of the fillers matter to the registries: all share the StructInfo and v-table
+//  of Template, sparing the compiler from instantiating them for each.
+struct Template;
+
+//  The roots of the fillers of each size, so that each size has its own types.
+struct Small;
+struct Medium;
+struct Large;
+
+//  Collects the ids of 2^n distinct types, nesting (P, u8) and (P, u16) n times over P.
+trait Fillers {
+    fn collect<P: 'static>(collector: &mut Vec<StructId>);
+}
+
+struct Zero;
+struct Double<F>(PhantomData<F>);
+
+impl Fillers for Zero {
+    fn collect<P: 'static>(collector: &mut Vec<StructId>) {
+        collector.push(poly::struct_id::<P>());
+    }
+}
+
+impl<F> Fillers for Double<F>
+    where F: Fillers
+{
+    fn collect<P: 'static>(collector: &mut Vec<StructId>) {
+        F::collect::<(P, u8)>(collector);
+        F::collect::<(P, u16)>(collector);
+    }
+}
+
+type Fillers4 = Double<Double<Double<Double<Zero>>>>;
+type Fillers10 = Double<Double<Double<Double<Double<Double<Fillers4>>>>>>;
+type Fillers16 = Double<Double<Double<Double<Double<Double<Fillers10>>>>>>;
+
+//  The ids of the fillers of the size measured.
+fn filler_ids() -> Vec<StructId> {
+    let mut ids = Vec::new();
+
+    match SIZE.load(Ordering::Relaxed) {
+    16 => Fillers4::collect::<Small>(&mut ids),
+    1024 => Fillers10::collect::<Medium>(&mut ids),
+    65536 => Fillers16::collect::<Large>(&mut ids),
+    size => panic!("unsupported size {}", size),
+    }
+
+    ids
+}
+
+fn no_offsets(_: StructId) -> &'static [isize] { &[] }
+
+fn register_struct_info(collector: &mut Vec<(StructId, StructInfo)>) {
+    collector.push(__private::make_struct_info::<Template>(no_offsets));
+
+    for id in filler_ids() {
+        collector.push((id, __private::make_struct_info::<Template>(no_offsets).1));
+    }
+}
+
+fn register_trait_info(collector: &mut Vec<(TraitId, TraitInfo)>) {
+    collector.push(__private::make_trait_info::<dyn Filler>());
+}
+
+fn register_vtables(tables: &mut VTableRegistryTables, _: &mut VTableRegistryIndices) {
+    let filler = poly::trait_id::<dyn Filler>();
+
+    for id in filler_ids() {
+        tables.push(((filler, id), Box::new([poly::make_vtable!(dyn Filler, Template)])));
+    }
+}
+
+__private::inventory::submit! {
+    Registration::new(register_struct_info, register_trait_info, register_vtables)
+}
+
+fn bench<F>(name: &str, mut f: F)
+    where F: FnMut()
+{
+    const ITERATIONS: u32 = 1_000_000;
+
+    //  Warm-up, which also initializes the registries.
+    for _ in 0..ITERATIONS / 10 { f(); }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS { f(); }
+    let elapsed = start.elapsed();
+
+    println!("{:<32} {:>8.1} ns/iter", name, elapsed.as_nanos() as f64 / ITERATIONS as f64);
+}
+
+fn run(size: usize) {
+    SIZE.store(size, Ordering::Relaxed);
+
+    let ids = filler_ids();
+
+    let filler = poly::trait_id::<dyn Filler>();
+    let samples = [("first", ids[0]), ("middle", ids[ids.len() / 2]), ("last", ids[ids.len() - 1])];
+
+    println!("{} registered structs", ids.len());
+
+    for &(name, id) in &samples {
+        bench(&format!("struct_info_by_id ({})", name), || {
+            black_box(poly::struct_info_by_id(black_box(id)));
+        });
+    }
+
+    for &(name, id) in &samples {
+        bench(&format!("v_table_by_id ({})", name), || {
+            black_box(poly::v_table_by_id(black_box(filler), black_box(id)));
+        });
+    }
+
+    bench("trait_info_by_id", || {
+        black_box(poly::trait_info_by_id(black_box(filler)));
+    });
+}
+
+fn main() {
+    //  `cargo bench` passes `--bench`, which is not a size.
+    if let Some(size) = env::args().skip(1).find_map(|a| a.parse().ok()) {
+        return run(size);
+    }
+
+    let exe = env::current_exe().expect("the path of the bench");
+
+    for (index, size) in SIZES.iter().enumerate() {
+        if index > 0 { println!(); }
+
+        let status = Command::new(&exe).arg(size.to_string()).status().expect("a run of the bench");
+        assert!(status.success(), "the bench failed for {} structs", size);
+    }
+}
@@ -0,0 +1,156 @@
+//
+//  Attributes reflected into typed fields
+//
+//  An element declares which of its fields reflect which attributes with
+//  `reflect_attributes!`, within its `impl Element`:
+//
+//      impl Element for HTMLVideoElement {
+//          reflect_attributes! {
+//              super _first_parent;
+//              "autoplay" => autoplay,
+//              "crossorigin" => cross_origin,
+//          }
+//      }
+//
+//  `ElementData::set_attribute` and `ElementData::remove_attribute` then keep the fields
+//  up to date, whichever the class of the element; `super` defers the other attributes
+//  to the parent struct.
+//
+//  The fields have no setters of their own: `ElementData::set_reflected` writes the typed
+//  value into the attribute, from which the field is updated, so that both always agree.
+//
+use std::cell::{Cell, RefCell};
+
+//  A typed field, updated with the value of its attribute, None when removed.
+pub trait Reflect {
+    fn update(&self, value: Option<&str>);
+}
+
+//  A typed value, as written into its attribute; None removes the attribute.
+pub trait AttributeValue {
+    fn to_attribute(&self) -> Option<String>;
+}
+
+//  Boolean attributes are present, with an empty value, or absent.
+impl AttributeValue for bool {
+    fn to_attribute(&self) -> Option<String> { self.then(String::new) }
+}
+
+impl AttributeValue for i64 {
+    fn to_attribute(&self) -> Option<String> { Some(self.to_string()) }
+}
+
+impl AttributeValue for &str {
+    fn to_attribute(&self) -> Option<String> { Some(self.to_string()) }
+}
+
+impl AttributeValue for Option<&str> {
+    fn to_attribute(&self) -> Option<String> { self.map(String::from) }
+}
+
+macro_rules! reflect_attributes(
+    (super $parent:ident; $($name:literal => $field:ident),* $(,)*) => {
+        fn reflected_attribute(&self, key: &str) -> Option<&dyn crate::attributes::Reflect> {
+            match key {
+            $($name => Some(&self.$field),)*
+            _ => crate::element::Element::reflected_attribute(&self.$parent, key),
+            }
+        }
+    };
+    ($($name:literal => $field:ident),* $(,)*) => {
+        fn reflected_attribute(&self, key: &str) -> Option<&dyn crate::attributes::Reflect> {
+            match key {
+            $($name => Some(&self.$field),)*
+            _ => None,
+            }
+        }
+    };
+);
+
+//  True if the attribute is present, whatever its value.
+#[derive(Clone, Debug, Default)]
+pub struct BoolAttribute(Cell<bool>);
+
+impl BoolAttribute {
+    pub fn get(&self) -> bool { self.0.get() }
+}
+
+impl Reflect for BoolAttribute {
+    fn update(&self, value: Option<&str>) { self.0.set(value.is_some()); }
+}
+
+//  An integer, or its default if the attribute is absent or invalid.
+#[derive(Clone, Debug)]
+pub struct IntAttribute {
+    value: Cell<i64>,
+    default: i64,
+}
+
+impl IntAttribute {
+    pub fn new(default: i64) -> IntAttribute { IntAttribute { value: Cell::new(default), default } }
+
+    pub fn get(&self) -> i64 { self.value.get() }
+}
+
+impl Reflect for IntAttribute {
+    fn update(&self, value: Option<&str>) {
+        let parsed = value.and_then(|v| v.trim().parse().ok());
+        self.value.set(parsed.unwrap_or(self.default));
+    }
+}
+
+//  One of a set of keywords, matched case-insensitively; distinct defaults apply when the
+//  attribute is absent, and when its value is not a keyword.
+#[derive(Clone, Debug)]
+pub struct EnumAttribute {
+    value: Cell<&'static str>,
+    keywords: &'static [&'static str],
+    missing: &'static str,
+    invalid: &'static str,
+}
+
+impl EnumAttribute {
+    pub fn new(keywords: &'static [&'static str], missing: &'static str, invalid: &'static str) -> EnumAttribute {
+        EnumAttribute { value: Cell::new(missing), keywords, missing, invalid }
+    }
+
+    pub fn get(&self) -> &'static str { self.value.get() }
+}
+
+impl Reflect for EnumAttribute {
+    fn update(&self, value: Option<&str>) {
+        let keyword = match value {
+        None => self.missing,
+        Some(v) => self.keywords.iter().find(|k| k.eq_ignore_ascii_case(v.trim())).copied().unwrap_or(self.invalid),
+        };
+
+        self.value.set(keyword);
+    }
+}
+
+//  A string; None if absent.
+#[derive(Clone, Debug, Default)]
+pub struct StringAttribute(RefCell<Option<String>>);
+
+impl StringAttribute {
+    pub fn get(&self) -> Option<String> { self.0.borrow().clone() }
+}
+
+impl Reflect for StringAttribute {
+    fn update(&self, value: Option<&str>) { *self.0.borrow_mut() = value.map(String::from); }
+}
+
+//  A URL, stripped of surrounding whitespace; None if absent or empty.
+#[derive(Clone, Debug, Default)]
+pub struct UrlAttribute(RefCell<Option<String>>);
+
+impl UrlAttribute {
+    pub fn get(&self) -> Option<String> { self.0.borrow().clone() }
+}
+
+impl Reflect for UrlAttribute {
+    fn update(&self, value: Option<&str>) {
+        let url = value.map(str::trim).filter(|v| !v.is_empty()).map(String::from);
+        *self.0.borrow_mut() = url;
+    }
+}
@@ -0,0 +1,165 @@
+//
+//  Element: the base of all elements
+//
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use poly::{poly_trait, PolyStruct};
+use poly::{Class, ExtendTrait, RcDyn, TraitExtendTrait, UniqueExtendStruct, UpCast};
+
+use crate::attributes::{AttributeValue, Reflect};
+use crate::mutation;
+use crate::node::{Node, NodeData, Tree};
+
+//
+//  ClassElement
+//
+pub type ClassElement = RcDyn<dyn Element, ElementData>;
+
+//  Elements are shared, hence the hooks only get `&self`, any state they maintain being
+//  held in cells.
+#[poly_trait]
+pub trait Element: Node {
+    fn do_the_thing(&self) {
+        let name = std::any::type_name::<Self>().rsplit("::").next().unwrap_or_default();
+        println!("{} is in da place!", name);
+    }
+
+    //  The tag name, in lower-case, as serialised.
+    fn tag_name(&self) -> &str;
+
+    fn before_set_attr(&self, _key: &str, _val: &str) {}
+    fn after_set_attr(&self, _key: &str, _val: &str) {}
+
+    //  Only invoked if the attribute is present.
+    fn before_remove_attr(&self, _key: &str) {}
+    fn after_remove_attr(&self, _key: &str) {}
+
+    //  The typed field reflecting the attribute `key`, if any; see `reflect_attributes!`.
+    fn reflected_attribute(&self, _key: &str) -> Option<&dyn Reflect> { None }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct ElementData {
+    //  Visible to the sub-classes of other modules, whose derive computes its offset.
+    #[parent]
+    pub(crate) _first_parent: NodeData,
+    pub tag: String,
+    attrs: RefCell<HashMap<String, String>>,
+}
+
+impl ElementData {
+    pub fn new(tag: &str) -> ElementData {
+        ElementData { _first_parent: NodeData::default(), tag: tag.to_string(), attrs: RefCell::default() }
+    }
+
+    pub fn node_data(&self) -> &NodeData { &self._first_parent }
+
+    //  Attribute names are case-insensitive, and stored in lower-case.
+    pub fn get_attribute(&self, key: &str) -> Option<String> {
+        self.attrs.borrow().get(&key.to_ascii_lowercase()).cloned()
+    }
+
+    pub fn has_attribute(&self, key: &str) -> bool {
+        self.attrs.borrow().contains_key(&key.to_ascii_lowercase())
+    }
+
+    //  The attributes, ordered by name.
+    pub fn attributes(&self) -> Vec<(String, String)> {
+        let mut attributes: Vec<_> = self.attrs.borrow().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        attributes.sort();
+        attributes
+    }
+
+    // Note: private access to ElementData::attrs, ensuring invariants; the hooks are
+    //       dispatched through the v-table of `element`, so that sub-classes see them.
+    pub fn set_attribute<T, S>(element: &RcDyn<T, S>, key: &str, value: &str)
+        where T: ?Sized + TraitExtendTrait<dyn Element> + 'static,
+              S: UniqueExtendStruct<ElementData> + 'static,
+    {
+        let element = element.as_element();
+        let key = &key.to_ascii_lowercase();
+
+        element.as_trait().before_set_attr(key, value);
+
+        let old = element.as_struct().attrs.borrow_mut().insert(key.to_string(), value.to_string());
+        if let Some(reflected) = element.as_trait().reflected_attribute(key) { reflected.update(Some(value)); }
+
+        element.as_trait().after_set_attr(key, value);
+
+        mutation::queue_attribute(&element.as_node(), key, old);
+    }
+
+    //  Returns the value of the removed attribute, if it was present.
+    pub fn remove_attribute<T, S>(element: &RcDyn<T, S>, key: &str) -> Option<String>
+        where T: ?Sized + TraitExtendTrait<dyn Element> + 'static,
+              S: UniqueExtendStruct<ElementData> + 'static,
+    {
+        let element = element.as_element();
+        let key = &key.to_ascii_lowercase();
+
+        if !element.as_struct().has_attribute(key) { return None; }
+
+        element.as_trait().before_remove_attr(key);
+
+        let old = element.as_struct().attrs.borrow_mut().remove(key);
+        if let Some(reflected) = element.as_trait().reflected_attribute(key) { reflected.update(None); }
+
+        element.as_trait().after_remove_attr(key);
+
+        mutation::queue_attribute(&element.as_node(), key, old.clone());
+
+        old
+    }
+
+    //  Sets the typed field reflecting `key` by writing `value` into the attribute, the field
+    //  then being updated from it, as for any other change of the attribute.
+    pub fn set_reflected<T, S, V>(element: &RcDyn<T, S>, key: &str, value: V)
+        where T: ?Sized + TraitExtendTrait<dyn Element> + 'static,
+              S: UniqueExtendStruct<ElementData> + 'static,
+              V: AttributeValue,
+    {
+        match value.to_attribute() {
+        Some(value) => ElementData::set_attribute(element, key, &value),
+        None => { ElementData::remove_attribute(element, key); },
+        }
+    }
+
+    //  Sets a boolean attribute to the empty string, or removes it.
+    pub fn toggle_attribute<T, S>(element: &RcDyn<T, S>, key: &str, on: bool)
+        where T: ?Sized + TraitExtendTrait<dyn Element> + 'static,
+              S: UniqueExtendStruct<ElementData> + 'static,
+    {
+        if on {
+            ElementData::set_attribute(element, key, "");
+        } else {
+            ElementData::remove_attribute(element, key);
+        }
+    }
+}
+
+impl Node for ElementData {}
+
+impl Element for ElementData {
+    fn tag_name(&self) -> &str { &self.tag }
+}
+
+//  Implemented for any handle on an element, whichever its class.
+pub trait AsElement {
+    fn as_element(&self) -> ClassElement;
+}
+
+impl<T: ?Sized, S> AsElement for RcDyn<T, S>
+    where T: TraitExtendTrait<dyn Element> + 'static,
+          S: UniqueExtendStruct<ElementData> + 'static,
+{
+    fn as_element(&self) -> ClassElement { self.clone().up_cast() }
+}
+
+//  Wraps a freshly built element into a node.
+pub fn new_element<S>(element: S) -> ClassElement
+    where S: ExtendTrait<dyn Element> + UniqueExtendStruct<ElementData> + 'static
+{
+    RcDyn::new(Class::new(element)).up_cast()
+}
@@ -0,0 +1,185 @@
+//
+//  Events: listeners, and their dispatch along the tree
+//
+//  An event is dispatched along the path from the root to its target: capturing
+//  listeners are invoked from the root down, then the listeners of the target itself,
+//  then, if the event bubbles, non-capturing listeners from the parent up. Unless the
+//  default is prevented, the default action is then left to the first node of the path
+//  which handles it, through `Node::default_action`.
+//
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::node::{ClassNode, Tree};
+
+pub type Listener = Rc<dyn Fn(&Event)>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Phase {
+    None,
+    Capturing,
+    AtTarget,
+    Bubbling,
+}
+
+pub struct Event {
+    kind: String,
+    bubbles: bool,
+    cancelable: bool,
+    phase: Cell<Phase>,
+    target: RefCell<Option<ClassNode>>,
+    current_target: RefCell<Option<ClassNode>>,
+    propagation_stopped: Cell<bool>,
+    immediate_propagation_stopped: Cell<bool>,
+    default_prevented: Cell<bool>,
+}
+
+impl Event {
+    pub fn new(kind: &str, bubbles: bool, cancelable: bool) -> Event {
+        Event {
+            kind: kind.to_string(),
+            bubbles,
+            cancelable,
+            phase: Cell::new(Phase::None),
+            target: RefCell::new(None),
+            current_target: RefCell::new(None),
+            propagation_stopped: Cell::new(false),
+            immediate_propagation_stopped: Cell::new(false),
+            default_prevented: Cell::new(false),
+        }
+    }
+
+    pub fn kind(&self) -> &str { &self.kind }
+
+    pub fn bubbles(&self) -> bool { self.bubbles }
+
+    pub fn cancelable(&self) -> bool { self.cancelable }
+
+    pub fn phase(&self) -> Phase { self.phase.get() }
+
+    pub fn target(&self) -> Option<ClassNode> { self.target.borrow().clone() }
+
+    //  The node whose listeners are being invoked.
+    pub fn current_target(&self) -> Option<ClassNode> { self.current_target.borrow().clone() }
+
+    pub fn stop_propagation(&self) { self.propagation_stopped.set(true); }
+
+    //  Also skips the remaining listeners of the current target.
+    pub fn stop_immediate_propagation(&self) {
+        self.propagation_stopped.set(true);
+        self.immediate_propagation_stopped.set(true);
+    }
+
+    //  Has no effect unless the event is cancelable.
+    pub fn prevent_default(&self) {
+        if self.cancelable { self.default_prevented.set(true); }
+    }
+
+    pub fn default_prevented(&self) -> bool { self.default_prevented.get() }
+}
+
+//  The listeners of a node, held by NodeData.
+#[derive(Default)]
+pub struct Listeners {
+    entries: RefCell<Vec<Entry>>,
+}
+
+struct Entry {
+    kind: String,
+    listener: Listener,
+    capture: bool,
+}
+
+impl Listeners {
+    //  A snapshot, so that listeners may be added or removed during dispatch.
+    fn matching(&self, kind: &str, phase: Phase) -> Vec<Listener> {
+        self.entries
+            .borrow()
+            .iter()
+            .filter(|e| e.kind == kind)
+            .filter(|e| match phase {
+            Phase::Capturing => e.capture,
+            Phase::Bubbling => !e.capture,
+            Phase::AtTarget | Phase::None => true,
+            })
+            .map(|e| e.listener.clone())
+            .collect()
+    }
+}
+
+//  Implemented for any handle on a node, whichever its class.
+pub trait EventTarget: Tree {
+    //  Adding the same listener twice, for the same kind and phase, has no effect.
+    fn add_event_listener(&self, kind: &str, listener: Listener, capture: bool) {
+        let node = self.as_node();
+        let mut entries = node.as_struct().listeners().entries.borrow_mut();
+
+        if entries.iter().any(|e| e.kind == kind && e.capture == capture && Rc::ptr_eq(&e.listener, &listener)) {
+            return;
+        }
+
+        entries.push(Entry { kind: kind.to_string(), listener, capture });
+    }
+
+    fn remove_event_listener(&self, kind: &str, listener: &Listener, capture: bool) {
+        let node = self.as_node();
+        let mut entries = node.as_struct().listeners().entries.borrow_mut();
+
+        entries.retain(|e| !(e.kind == kind && e.capture == capture && Rc::ptr_eq(&e.listener, listener)));
+    }
+
+    //  Dispatches `event` with this node as target; returns false if its default was
+    //  prevented.
+    //
+    //  The state left by a previous dispatch is reset first, so the same event may be
+    //  dispatched again.
+    fn dispatch_event(&self, event: &Event) -> bool {
+        let target = self.as_node();
+        let path: Vec<ClassNode> = target.ancestors().collect();
+
+        event.phase.set(Phase::None);
+        event.propagation_stopped.set(false);
+        event.immediate_propagation_stopped.set(false);
+        event.default_prevented.set(false);
+        *event.current_target.borrow_mut() = None;
+        *event.target.borrow_mut() = Some(target.clone());
+
+        for node in path.iter().rev() {
+            invoke(node, event, Phase::Capturing);
+        }
+
+        invoke(&target, event, Phase::AtTarget);
+
+        if event.bubbles {
+            for node in &path {
+                invoke(node, event, Phase::Bubbling);
+            }
+        }
+
+        event.phase.set(Phase::None);
+        *event.current_target.borrow_mut() = None;
+
+        if !event.default_prevented() {
+            let bubbled = if event.bubbles { path.len() } else { 0 };
+            for node in Some(target).into_iter().chain(path.into_iter().take(bubbled)) {
+                if node.as_trait().default_action(event) { break; }
+            }
+        }
+
+        !event.default_prevented()
+    }
+}
+
+impl<N: Tree + ?Sized> EventTarget for N {}
+
+fn invoke(node: &ClassNode, event: &Event, phase: Phase) {
+    if event.propagation_stopped.get() { return; }
+
+    event.phase.set(phase);
+    *event.current_target.borrow_mut() = Some(node.clone());
+
+    for listener in node.as_struct().listeners().matching(&event.kind, phase) {
+        if event.immediate_propagation_stopped.get() { break; }
+        listener(event);
+    }
+}
@@ -0,0 +1,785 @@
+//
+//  HTML elements
+//
+//  Shared fields are factored through struct extension, e.g. HTMLMediaElementData is the
+//  parent of both HTMLVideoElement and HTMLAudioElement, and shared behaviour through trait
+//  extension, e.g. MediaElement extends Element with playback.
+//
+//  Each class must be registered with `poly_hierarchy!`, along with the traits it
+//  implements, for nodes to be down-cast to it.
+//
+use std::cell::{Cell, RefCell};
+
+use poly::{poly_trait, DownCast, PolyStruct, RcDyn, TraitExtendTrait, UniqueExtendStruct};
+
+use crate::attributes::{BoolAttribute, EnumAttribute, IntAttribute, StringAttribute, UrlAttribute};
+use crate::element::{new_element, ClassElement, Element, ElementData};
+use crate::events::Event;
+use crate::node::{ClassNode, Node, NodeData, Tree};
+use crate::parser::TagRegistry;
+
+//  Registers the classes of this module, by tag.
+pub fn register(registry: &mut TagRegistry) {
+    registry.register("a", |_, _| new_element(HTMLAnchorElement::default()));
+    registry.register("img", |_, _| new_element(HTMLImageElement::default()));
+    registry.register("audio", |_, _| new_element(HTMLAudioElement::default()));
+    registry.register("video", |_, _| new_element(HTMLVideoElement::default()));
+    registry.register("form", |_, _| new_element(HTMLFormElement::default()));
+    registry.register("input", new_input);
+    registry.register("select", |_, _| new_element(HTMLSelectElement::default()));
+    registry.register("option", |_, _| new_element(HTMLOptionElement::default()));
+    registry.register("table", |_, _| new_element(HTMLTableElement::default()));
+    registry.register("tr", |_, _| new_element(HTMLTableRowElement::default()));
+
+    for tag in ["thead", "tbody", "tfoot"] {
+        registry.register(tag, |tag, _| new_element(HTMLTableSectionElement::new(tag)));
+    }
+
+    for tag in ["td", "th"] {
+        registry.register(tag, |tag, _| new_element(HTMLTableCellElement::new(tag)));
+    }
+}
+
+//  The children of `element` of class C.
+fn children_of<C>(element: &ElementData) -> Vec<C>
+    where ClassNode: DownCast<C>
+{
+    let mut children = Vec::new();
+    let mut child = element.node_data().first_child();
+
+    while let Some(node) = child {
+        child = node.next_sibling();
+        if let Ok(c) = node.down_cast() { children.push(c); }
+    }
+
+    children
+}
+
+//
+//  HTMLAnchorElement
+//
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLAnchorElement {
+    #[parent]
+    _first_parent: ElementData,
+    pub href: UrlAttribute,
+    //  Where the last click led.
+    pub followed: RefCell<Option<String>>,
+}
+
+impl Default for HTMLAnchorElement {
+    fn default() -> HTMLAnchorElement {
+        HTMLAnchorElement { _first_parent: ElementData::new("a"), href: UrlAttribute::default(), followed: RefCell::default() }
+    }
+}
+
+impl Node for HTMLAnchorElement {
+    fn default_action(&self, event: &Event) -> bool {
+        if event.kind() != "click" { return false; }
+
+        match self.href.get() {
+        Some(href) => { *self.followed.borrow_mut() = Some(href); true },
+        None => false,
+        }
+    }
+}
+
+impl Element for HTMLAnchorElement {
+    fn tag_name(&self) -> &str { "a" }
+
+    reflect_attributes! {
+        super _first_parent;
+        "href" => href,
+    }
+}
+
+//
+//  HTMLImageElement
+//
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLImageElement {
+    #[parent]
+    _first_parent: ElementData,
+    pub src: UrlAttribute,
+    pub width: IntAttribute,
+    pub height: IntAttribute,
+}
+
+impl Default for HTMLImageElement {
+    fn default() -> HTMLImageElement {
+        HTMLImageElement {
+            _first_parent: ElementData::new("img"),
+            src: UrlAttribute::default(),
+            width: IntAttribute::new(0),
+            height: IntAttribute::new(0),
+        }
+    }
+}
+
+impl Node for HTMLImageElement {}
+
+impl Element for HTMLImageElement {
+    fn tag_name(&self) -> &str { "img" }
+
+    fn before_set_attr(&self, key: &str, val: &str) {
+        if key == "src" {
+            // remove cached image
+        }
+        <ElementData as Element>::before_set_attr(&self._first_parent, key, val);
+    }
+
+    reflect_attributes! {
+        super _first_parent;
+        "src" => src,
+        "width" => width,
+        "height" => height,
+    }
+}
+
+//
+//  Media: HTMLMediaElementData, HTMLAudioElement and HTMLVideoElement
+//
+pub type ClassMedia = RcDyn<dyn MediaElement, HTMLMediaElementData>;
+
+#[poly_trait]
+pub trait MediaElement: Element {
+    fn media(&self) -> &HTMLMediaElementData;
+
+    //  Whether media of the MIME type `mime` may be played.
+    fn can_play_type(&self, _mime: &str) -> bool { false }
+
+    fn play(&self) { self.media().paused.set(false); }
+
+    fn pause(&self) { self.media().paused.set(true); }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLMediaElementData {
+    #[parent]
+    _first_parent: ElementData,
+    pub src: UrlAttribute,
+    pub autoplay: BoolAttribute,
+    pub muted: BoolAttribute,
+    pub looping: BoolAttribute,
+    pub cross_origin: EnumAttribute,
+    pub paused: Cell<bool>,
+}
+
+impl HTMLMediaElementData {
+    pub fn new(tag: &str) -> HTMLMediaElementData {
+        HTMLMediaElementData {
+            _first_parent: ElementData::new(tag),
+            src: UrlAttribute::default(),
+            autoplay: BoolAttribute::default(),
+            muted: BoolAttribute::default(),
+            looping: BoolAttribute::default(),
+            //  An invalid value, such as "true", means anonymous.
+            cross_origin: EnumAttribute::new(&["anonymous", "use-credentials"], "", "anonymous"),
+            paused: Cell::new(true),
+        }
+    }
+}
+
+//  Clicking a media element toggles its playback, through its own `play` and `pause`.
+fn toggle_playback<M: MediaElement>(media: &M, event: &Event) -> bool {
+    if event.kind() != "click" { return false; }
+
+    if media.media().paused.get() { media.play(); } else { media.pause(); }
+    true
+}
+
+impl Node for HTMLMediaElementData {
+    fn default_action(&self, event: &Event) -> bool { toggle_playback(self, event) }
+}
+
+impl Element for HTMLMediaElementData {
+    fn tag_name(&self) -> &str { self._first_parent.tag_name() }
+
+    reflect_attributes! {
+        super _first_parent;
+        "src" => src,
+        "autoplay" => autoplay,
+        "muted" => muted,
+        "loop" => looping,
+        "crossorigin" => cross_origin,
+    }
+}
+
+impl MediaElement for HTMLMediaElementData {
+    fn media(&self) -> &HTMLMediaElementData { self }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLAudioElement {
+    #[parent]
+    _first_parent: HTMLMediaElementData,
+}
+
+impl Default for HTMLAudioElement {
+    fn default() -> HTMLAudioElement {
+        HTMLAudioElement { _first_parent: HTMLMediaElementData::new("audio") }
+    }
+}
+
+impl Node for HTMLAudioElement {
+    fn default_action(&self, event: &Event) -> bool { toggle_playback(self, event) }
+}
+
+impl Element for HTMLAudioElement {
+    fn tag_name(&self) -> &str { "audio" }
+
+    reflect_attributes! {
+        super _first_parent;
+    }
+}
+
+impl MediaElement for HTMLAudioElement {
+    fn media(&self) -> &HTMLMediaElementData { &self._first_parent }
+
+    fn can_play_type(&self, mime: &str) -> bool { mime.starts_with("audio/") }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLVideoElement {
+    #[parent]
+    _first_parent: HTMLMediaElementData,
+    pub poster: UrlAttribute,
+    pub width: IntAttribute,
+    pub height: IntAttribute,
+}
+
+impl Default for HTMLVideoElement {
+    fn default() -> HTMLVideoElement {
+        HTMLVideoElement {
+            _first_parent: HTMLMediaElementData::new("video"),
+            poster: UrlAttribute::default(),
+            width: IntAttribute::new(0),
+            height: IntAttribute::new(0),
+        }
+    }
+}
+
+impl Node for HTMLVideoElement {
+    fn default_action(&self, event: &Event) -> bool { toggle_playback(self, event) }
+}
+
+impl Element for HTMLVideoElement {
+    fn tag_name(&self) -> &str { "video" }
+
+    reflect_attributes! {
+        super _first_parent;
+        "poster" => poster,
+        "width" => width,
+        "height" => height,
+    }
+}
+
+impl MediaElement for HTMLVideoElement {
+    fn media(&self) -> &HTMLMediaElementData { &self._first_parent }
+
+    fn can_play_type(&self, mime: &str) -> bool { mime.starts_with("video/") || mime.starts_with("audio/") }
+}
+
+//
+//  Forms: HTMLFormElement, and its controls
+//
+pub type ClassForm = RcDyn<dyn Element, HTMLFormElement>;
+pub type ClassFormControl = RcDyn<dyn FormControl, ElementData>;
+
+#[poly_trait]
+pub trait FormControl: Element {
+    //  The value submitted with the form.
+    fn value(&self) -> String;
+
+    //  Whether the control contributes to the entries of its form.
+    fn is_submittable(&self) -> bool { true }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLFormElement {
+    #[parent]
+    _first_parent: ElementData,
+    pub action: UrlAttribute,
+    pub method: EnumAttribute,
+}
+
+impl HTMLFormElement {
+    //  The (name, value) pairs of the named, submittable, controls of `form`, in tree order.
+    pub fn entries(form: &ClassForm) -> Vec<(String, String)> {
+        form.descendants_of::<ClassFormControl>()
+            .filter(|control| control.as_trait().is_submittable())
+            .filter_map(|control| {
+                let name = control.as_struct().get_attribute("name")?;
+                Some((name, control.as_trait().value()))
+            })
+            .collect()
+    }
+}
+
+impl Default for HTMLFormElement {
+    fn default() -> HTMLFormElement {
+        HTMLFormElement {
+            _first_parent: ElementData::new("form"),
+            action: UrlAttribute::default(),
+            method: EnumAttribute::new(&["get", "post", "dialog"], "get", "get"),
+        }
+    }
+}
+
+impl Node for HTMLFormElement {}
+
+impl Element for HTMLFormElement {
+    fn tag_name(&self) -> &str { "form" }
+
+    reflect_attributes! {
+        super _first_parent;
+        "action" => action,
+        "method" => method,
+    }
+}
+
+//  The input class depends on its type, when parsed; an input does not change class when
+//  its type changes later on.
+//
+//  Radio buttons have no class of their own yet, their checkedness depending on the other
+//  buttons of their group; they are plain inputs, never submitted.
+fn new_input(_: &str, attributes: &[(String, String)]) -> ClassElement {
+    let kind = attributes.iter().find(|(key, _)| key == "type").map(|(_, value)| value.trim().to_ascii_lowercase());
+
+    match kind.as_deref() {
+    Some("checkbox") => new_element(HTMLCheckboxInputElement::default()),
+    None | Some("text" | "search" | "email" | "password" | "url" | "tel") => new_element(HTMLTextInputElement::default()),
+    Some(_) => new_element(HTMLInputElementData::default()),
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLInputElementData {
+    #[parent]
+    _first_parent: ElementData,
+    pub kind: EnumAttribute,
+    pub default_value: StringAttribute,
+    pub disabled: BoolAttribute,
+}
+
+impl HTMLInputElementData {
+    //  Sets the `value` attribute, whichever the class of the input.
+    pub fn set_value<T, S>(input: &RcDyn<T, S>, value: &str)
+        where T: ?Sized + TraitExtendTrait<dyn Element> + 'static,
+              S: UniqueExtendStruct<HTMLInputElementData> + UniqueExtendStruct<ElementData> + 'static,
+    {
+        ElementData::set_reflected(input, "value", value);
+    }
+
+    pub fn set_disabled<T, S>(input: &RcDyn<T, S>, disabled: bool)
+        where T: ?Sized + TraitExtendTrait<dyn Element> + 'static,
+              S: UniqueExtendStruct<HTMLInputElementData> + UniqueExtendStruct<ElementData> + 'static,
+    {
+        ElementData::set_reflected(input, "disabled", disabled);
+    }
+}
+
+impl Default for HTMLInputElementData {
+    fn default() -> HTMLInputElementData {
+        const KINDS: &[&str] = &[
+            "text", "search", "email", "password", "url", "tel", "number",
+            "checkbox", "radio", "hidden", "submit", "reset", "button",
+        ];
+
+        HTMLInputElementData {
+            _first_parent: ElementData::new("input"),
+            kind: EnumAttribute::new(KINDS, "text", "text"),
+            default_value: StringAttribute::default(),
+            disabled: BoolAttribute::default(),
+        }
+    }
+}
+
+impl Node for HTMLInputElementData {}
+
+impl Element for HTMLInputElementData {
+    fn tag_name(&self) -> &str { "input" }
+
+    reflect_attributes! {
+        super _first_parent;
+        "type" => kind,
+        "value" => default_value,
+        "disabled" => disabled,
+    }
+}
+
+impl FormControl for HTMLInputElementData {
+    fn value(&self) -> String { self.default_value.get().unwrap_or_default() }
+
+    fn is_submittable(&self) -> bool {
+        !self.disabled.get() && !matches!(self.kind.get(), "submit" | "reset" | "button" | "radio")
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLTextInputElement {
+    #[parent]
+    _first_parent: HTMLInputElementData,
+    pub max_length: IntAttribute,
+    pub placeholder: StringAttribute,
+}
+
+impl Default for HTMLTextInputElement {
+    fn default() -> HTMLTextInputElement {
+        HTMLTextInputElement {
+            _first_parent: HTMLInputElementData::default(),
+            max_length: IntAttribute::new(-1),
+            placeholder: StringAttribute::default(),
+        }
+    }
+}
+
+impl Node for HTMLTextInputElement {}
+
+impl Element for HTMLTextInputElement {
+    fn tag_name(&self) -> &str { "input" }
+
+    reflect_attributes! {
+        super _first_parent;
+        "maxlength" => max_length,
+        "placeholder" => placeholder,
+    }
+}
+
+impl FormControl for HTMLTextInputElement {
+    //  `maxlength` only restricts what the user may type, not the value submitted.
+    fn value(&self) -> String { self._first_parent.value() }
+
+    fn is_submittable(&self) -> bool { self._first_parent.is_submittable() }
+}
+
+//  Checkboxes.
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLCheckboxInputElement {
+    #[parent]
+    _first_parent: HTMLInputElementData,
+    pub default_checked: BoolAttribute,
+    //  Set once the user changes the checkedness, overriding `default_checked`.
+    dirty_checked: Cell<Option<bool>>,
+}
+
+impl HTMLCheckboxInputElement {
+    pub fn checked(&self) -> bool { self.dirty_checked.get().unwrap_or(self.default_checked.get()) }
+
+    //  Sets the `checked` attribute, which the checkedness follows from then on, even if
+    //  changed by a click before.
+    pub fn set_checked(checkbox: &RcDyn<dyn Element, HTMLCheckboxInputElement>, checked: bool) {
+        checkbox.as_struct().dirty_checked.set(None);
+        ElementData::set_reflected(checkbox, "checked", checked);
+    }
+}
+
+impl Default for HTMLCheckboxInputElement {
+    fn default() -> HTMLCheckboxInputElement {
+        HTMLCheckboxInputElement {
+            _first_parent: HTMLInputElementData::default(),
+            default_checked: BoolAttribute::default(),
+            dirty_checked: Cell::new(None),
+        }
+    }
+}
+
+impl Node for HTMLCheckboxInputElement {
+    //  Clicking toggles the checkedness, unless disabled.
+    fn default_action(&self, event: &Event) -> bool {
+        if event.kind() != "click" || self._first_parent.disabled.get() { return false; }
+
+        self.dirty_checked.set(Some(!self.checked()));
+        true
+    }
+}
+
+impl Element for HTMLCheckboxInputElement {
+    fn tag_name(&self) -> &str { "input" }
+
+    reflect_attributes! {
+        super _first_parent;
+        "checked" => default_checked,
+    }
+}
+
+impl FormControl for HTMLCheckboxInputElement {
+    fn value(&self) -> String { self._first_parent.default_value.get().unwrap_or_else(|| "on".to_string()) }
+
+    fn is_submittable(&self) -> bool { self.checked() && self._first_parent.is_submittable() }
+}
+
+//
+//  Selects: HTMLSelectElement and HTMLOptionElement
+//
+pub type ClassOption = RcDyn<dyn Element, HTMLOptionElement>;
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLSelectElement {
+    #[parent]
+    _first_parent: ElementData,
+    pub multiple: BoolAttribute,
+    pub disabled: BoolAttribute,
+}
+
+impl HTMLSelectElement {
+    //  The options, including those within an optgroup.
+    pub fn options(&self) -> Vec<ClassOption> {
+        let mut options = Vec::new();
+        let mut child = self._first_parent.node_data().first_child();
+
+        while let Some(node) = child {
+            child = node.next_sibling();
+
+            let group: Option<ClassElement> = node.clone().down_cast().ok().filter(|e: &ClassElement| {
+                e.as_trait().tag_name() == "optgroup"
+            });
+
+            match group {
+            Some(group) => options.extend(children_of::<ClassOption>(group.as_struct())),
+            None => options.extend(node.down_cast().ok()),
+            }
+        }
+
+        options
+    }
+
+    //  The first selected option or, unless multiple, the first option.
+    pub fn selected_index(&self) -> Option<usize> {
+        let options = self.options();
+
+        options.iter().position(|o| o.as_struct().selected()).or_else(|| {
+            (!self.multiple.get() && !options.is_empty()).then_some(0)
+        })
+    }
+
+    //  Selects the option at `index`, deselecting the others unless multiple.
+    pub fn select(&self, index: usize) {
+        for (i, option) in self.options().iter().enumerate() {
+            if i == index {
+                HTMLOptionElement::set_selected(option, true);
+            } else if !self.multiple.get() {
+                HTMLOptionElement::set_selected(option, false);
+            }
+        }
+    }
+}
+
+impl Default for HTMLSelectElement {
+    fn default() -> HTMLSelectElement {
+        HTMLSelectElement {
+            _first_parent: ElementData::new("select"),
+            multiple: BoolAttribute::default(),
+            disabled: BoolAttribute::default(),
+        }
+    }
+}
+
+impl Node for HTMLSelectElement {}
+
+impl Element for HTMLSelectElement {
+    fn tag_name(&self) -> &str { "select" }
+
+    reflect_attributes! {
+        super _first_parent;
+        "multiple" => multiple,
+        "disabled" => disabled,
+    }
+}
+
+impl FormControl for HTMLSelectElement {
+    fn value(&self) -> String {
+        self.selected_index().map(|i| self.options()[i].as_struct().value()).unwrap_or_default()
+    }
+
+    fn is_submittable(&self) -> bool { !self.disabled.get() && self.selected_index().is_some() }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLOptionElement {
+    #[parent]
+    _first_parent: ElementData,
+    pub default_selected: BoolAttribute,
+    pub value_attribute: StringAttribute,
+}
+
+impl HTMLOptionElement {
+    pub fn selected(&self) -> bool { self.default_selected.get() }
+
+    //  Sets the `selected` attribute; see also HTMLSelectElement::select.
+    pub fn set_selected(option: &ClassOption, selected: bool) {
+        ElementData::set_reflected(option, "selected", selected);
+    }
+
+    pub fn set_value(option: &ClassOption, value: Option<&str>) {
+        ElementData::set_reflected(option, "value", value);
+    }
+
+    //  The value attribute or, if absent, the text of the option.
+    pub fn value(&self) -> String {
+        self.value_attribute.get().unwrap_or_else(|| self._first_parent.node_data().text_content().trim().to_string())
+    }
+}
+
+impl Default for HTMLOptionElement {
+    fn default() -> HTMLOptionElement {
+        HTMLOptionElement {
+            _first_parent: ElementData::new("option"),
+            default_selected: BoolAttribute::default(),
+            value_attribute: StringAttribute::default(),
+        }
+    }
+}
+
+impl Node for HTMLOptionElement {}
+
+impl Element for HTMLOptionElement {
+    fn tag_name(&self) -> &str { "option" }
+
+    reflect_attributes! {
+        super _first_parent;
+        "selected" => default_selected,
+        "value" => value_attribute,
+    }
+}
+
+//
+//  Tables: HTMLTableElement, HTMLTableSectionElement, HTMLTableRowElement and
+//  HTMLTableCellElement
+//
+pub type ClassTable = RcDyn<dyn Element, HTMLTableElement>;
+pub type ClassTableSection = RcDyn<dyn Element, HTMLTableSectionElement>;
+pub type ClassTableRow = RcDyn<dyn Element, HTMLTableRowElement>;
+pub type ClassTableCell = RcDyn<dyn Element, HTMLTableCellElement>;
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLTableElement {
+    #[parent]
+    _first_parent: ElementData,
+}
+
+impl HTMLTableElement {
+    //  The rows of the head, then those of the bodies or the table itself, then those of
+    //  the foot.
+    pub fn rows(&self) -> Vec<ClassTableRow> {
+        let (mut head, mut body, mut foot) = (Vec::new(), Vec::new(), Vec::new());
+        let mut child = self._first_parent.node_data().first_child();
+
+        while let Some(node) = child {
+            child = node.next_sibling();
+
+            let section: Option<ClassTableSection> = node.clone().down_cast().ok();
+
+            match section {
+            Some(section) => {
+                let rows = section.as_struct().rows();
+                match section.as_trait().tag_name() {
+                "thead" => head.extend(rows),
+                "tfoot" => foot.extend(rows),
+                _ => body.extend(rows),
+                }
+            },
+            None => body.extend(node.down_cast().ok()),
+            }
+        }
+
+        head.into_iter().chain(body).chain(foot).collect()
+    }
+}
+
+impl Default for HTMLTableElement {
+    fn default() -> HTMLTableElement { HTMLTableElement { _first_parent: ElementData::new("table") } }
+}
+
+impl Node for HTMLTableElement {}
+
+impl Element for HTMLTableElement {
+    fn tag_name(&self) -> &str { "table" }
+}
+
+//  A thead, tbody or tfoot.
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLTableSectionElement {
+    #[parent]
+    _first_parent: ElementData,
+}
+
+impl HTMLTableSectionElement {
+    pub fn new(tag: &str) -> HTMLTableSectionElement {
+        HTMLTableSectionElement { _first_parent: ElementData::new(tag) }
+    }
+
+    pub fn rows(&self) -> Vec<ClassTableRow> { children_of(&self._first_parent) }
+}
+
+impl Node for HTMLTableSectionElement {}
+
+impl Element for HTMLTableSectionElement {
+    fn tag_name(&self) -> &str { self._first_parent.tag_name() }
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLTableRowElement {
+    #[parent]
+    _first_parent: ElementData,
+}
+
+impl HTMLTableRowElement {
+    pub fn cells(&self) -> Vec<ClassTableCell> { children_of(&self._first_parent) }
+}
+
+impl Default for HTMLTableRowElement {
+    fn default() -> HTMLTableRowElement { HTMLTableRowElement { _first_parent: ElementData::new("tr") } }
+}
+
+impl Node for HTMLTableRowElement {}
+
+impl Element for HTMLTableRowElement {
+    fn tag_name(&self) -> &str { "tr" }
+}
+
+//  A td or th.
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct HTMLTableCellElement {
+    #[parent]
+    _first_parent: ElementData,
+    pub col_span: IntAttribute,
+    pub row_span: IntAttribute,
+}
+
+impl HTMLTableCellElement {
+    pub fn new(tag: &str) -> HTMLTableCellElement {
+        HTMLTableCellElement {
+            _first_parent: ElementData::new(tag),
+            col_span: IntAttribute::new(1),
+            row_span: IntAttribute::new(1),
+        }
+    }
+}
+
+impl Node for HTMLTableCellElement {}
+
+impl Element for HTMLTableCellElement {
+    fn tag_name(&self) -> &str { self._first_parent.tag_name() }
+
+    reflect_attributes! {
+        super _first_parent;
+        "colspan" => col_span,
+        "rowspan" => row_span,
+    }
+}
@@ -0,0 +1,582 @@
+//
+//  Alright let's implement that DOM's example
+//
+#![allow(dead_code)]
+
+#[macro_use]
+mod node;
+#[macro_use]
+mod attributes;
+#[macro_use]
+mod element;
+#[macro_use]
+mod html;
+mod events;
+mod mutation;
+mod parser;
+mod selector;
+mod serializer;
+
+use poly::poly_hierarchy;
+use poly::{Class, DownCast, RcDyn, UpCast};
+
+pub use element::{AsElement, ClassElement, Element, ElementData};
+pub use html::{ClassForm, ClassFormControl, ClassMedia, ClassOption, FormControl, MediaElement};
+pub use html::{ClassTable, ClassTableCell, ClassTableRow, ClassTableSection};
+pub use html::{HTMLAnchorElement, HTMLAudioElement, HTMLImageElement, HTMLMediaElementData, HTMLVideoElement};
+pub use html::{HTMLCheckboxInputElement, HTMLFormElement, HTMLInputElementData, HTMLTextInputElement};
+pub use html::{HTMLOptionElement, HTMLSelectElement};
+pub use html::{HTMLTableCellElement, HTMLTableElement, HTMLTableRowElement, HTMLTableSectionElement};
+pub use selector::{Matches, Query, SelectorError};
+pub use serializer::Html;
+pub use events::{Event, EventTarget, Listener, Phase};
+pub use mutation::{MutationKind, MutationObserver, MutationRecord, ObserverOptions};
+pub use node::{ClassDocument, ClassNode, ClassText, DocumentData, DomError, Node, NodeData, TextNode, Tree};
+
+//  Builds and parses a couple of documents, describing them; the checks are run by
+//  `cargo test`, see the #[test] functions below.
+fn main() {
+    let body = element("body");
+    let video: RcDyn<dyn Element, HTMLVideoElement> = RcDyn::new(Class::new(HTMLVideoElement::default()));
+    let image: RcDyn<dyn Element, HTMLImageElement> = RcDyn::new(Class::new(HTMLImageElement::default()));
+
+    body.append_child(text("Hello, ")).expect("append hello");
+    body.append_child(video.as_node()).expect("append video");
+    video.append_child(image.as_node()).expect("append image");
+    body.append_child(text("World!")).expect("append world");
+
+    println!("Built:");
+    describe(&body, 1);
+
+    //  Each element, whichever its class, is handled through its Element v-table.
+    for element in body.descendants_of::<ClassElement>() {
+        process_any_element(element.as_trait());
+    }
+
+    let document = parser::parse(DOCUMENT);
+
+    println!("Parsed:");
+    describe(&document.as_node(), 1);
+    println!("Serialized: {}", document.outer_html());
+}
+
+const DOCUMENT: &str = "<!DOCTYPE html>\
+    <body><p>Fish &amp; chips<p>A <b>bold<i> move</b>!</i>\
+    <video crossOrigin=true src='a.mp4'><img src=\"poster.png\"></video>\
+    <p class=note>1 < 2</div></body>";
+
+fn process_any_element(element: &dyn Element) {
+    println!("Process an element!");
+    element.do_the_thing();
+}
+
+fn text(text: &str) -> ClassNode {
+    let text: ClassText = RcDyn::new(Class::new(TextNode::new(text)));
+    text.up_cast()
+}
+
+fn element(tag: &str) -> ClassNode {
+    let element: ClassElement = RcDyn::new(Class::new(ElementData::new(tag)));
+    element.up_cast()
+}
+
+//  Checks that the links of the children of `parent` agree with one another.
+fn check_links(parent: &ClassNode) {
+    let mut previous: Option<ClassNode> = None;
+
+    for node in parent.children() {
+        assert!(RcDyn::ptr_eq(&node.parent().expect("parent"), parent));
+
+        match (&previous, node.previous_sibling()) {
+        (Some(p), Some(q)) => assert!(RcDyn::ptr_eq(p, &q)),
+        (None, None) => {}
+        _ => panic!("inconsistent previous sibling"),
+        }
+
+        previous = Some(node);
+    }
+
+    match (&previous, parent.last_child()) {
+    (Some(p), Some(q)) => assert!(RcDyn::ptr_eq(p, &q)),
+    (None, None) => {}
+    _ => panic!("inconsistent last child"),
+    }
+}
+
+fn describe(node: &ClassNode, depth: usize) {
+    let indent = "  ".repeat(depth);
+
+    let text: Option<ClassText> = node.clone().down_cast().ok();
+    let element: Option<ClassElement> = node.clone().down_cast().ok();
+    let document: Option<ClassDocument> = node.clone().down_cast().ok();
+
+    match (text, element, document) {
+    (Some(text), _, _) => println!("{}{:?}", indent, text.as_struct().text()),
+    (_, Some(element), _) => println!("{}<{}>", indent, element.as_trait().tag_name()),
+    (_, _, Some(_)) => println!("{}#document", indent),
+    _ => println!("{}Oh shoot, nothing I know!", indent),
+    }
+
+    for child in node.children() {
+        describe(&child, depth + 1);
+    }
+}
+
+#[test]
+fn build_tree() {
+    let body = element("body");
+
+    let video: RcDyn<dyn Element, HTMLVideoElement> = RcDyn::new(Class::new(HTMLVideoElement::default()));
+
+    //  The reflection of HTMLVideoElement runs, even though set_attribute knows only ElementData.
+    ElementData::set_attribute(&video, "crossOrigin", "true");
+    assert_eq!(video.as_struct().media().cross_origin.get(), "anonymous");
+
+    process_any_element(video.as_trait());
+
+    let hello = text("Hello, ");
+    let world = text("World!");
+    let image: RcDyn<dyn Element, HTMLImageElement> = RcDyn::new(Class::new(HTMLImageElement::default()));
+    let image = image.as_node();
+
+    body.append_child(hello.clone()).expect("append hello");
+    body.append_child(world.clone()).expect("append world");
+    body.insert_before(video.as_node(), Some(&world)).expect("insert video");
+    video.append_child(image.clone()).expect("append image");
+    check_links(&body);
+    check_links(&video.as_node());
+
+    println!("Built:");
+    describe(&body, 1);
+
+    let tags: Vec<_> = body.descendants_of::<ClassElement>().map(|e| e.as_trait().tag_name().to_string()).collect();
+    assert_eq!(tags, ["video", "img"]);
+
+    let texts: Vec<_> = body.descendants_of::<ClassText>().map(|t| t.as_struct().text()).collect();
+    assert_eq!(texts, ["Hello, ", "World!"]);
+
+    assert_eq!(image.ancestors().count(), 2);
+    assert_eq!(hello.following_siblings().count(), 2);
+    assert_eq!(hello.following_siblings_of::<ClassText>().count(), 1);
+
+    //  Each element, whichever its class, is handled through its Element v-table.
+    for element in body.descendants_of::<ClassElement>() {
+        process_any_element(element.as_trait());
+    }
+
+    //  A node cannot be inserted within itself, nor within a text node.
+    assert_eq!(image.append_child(body.clone()).err(), Some(DomError::HierarchyRequest));
+    assert_eq!(hello.append_child(text("!")).err(), Some(DomError::HierarchyRequest));
+    assert_eq!(body.remove_child(&image).err(), Some(DomError::NotFound));
+
+    //  Moving a node detaches it from its former parent.
+    body.replace_child(image.clone(), &hello).expect("replace hello");
+    assert!(hello.parent().is_none());
+    assert!(video.first_child().is_none());
+    check_links(&body);
+
+    body.remove_child(&world).expect("remove world");
+    assert!(world.parent().is_none() && world.previous_sibling().is_none());
+    check_links(&body);
+
+    println!("Rearranged:");
+    describe(&body, 1);
+}
+
+#[test]
+fn parse_document() {
+    let document = parser::parse(DOCUMENT);
+
+    let tags: Vec<_> = document.descendants_of::<ClassElement>().map(|e| e.as_trait().tag_name().to_string()).collect();
+    assert_eq!(tags, ["body", "p", "p", "b", "i", "video", "img", "p"]);
+
+    let video: RcDyn<dyn Element, HTMLVideoElement> =
+        document.descendants_of().next().expect("a video element");
+    assert_eq!(video.as_struct().media().cross_origin.get(), "anonymous");
+    assert_eq!(video.as_struct().media().src.get().as_deref(), Some("a.mp4"));
+    assert_eq!(video.as_element().as_struct().get_attribute("src").as_deref(), Some("a.mp4"));
+
+    let texts: Vec<_> = document.descendants_of::<ClassText>().map(|t| t.as_struct().text()).collect();
+    assert_eq!(texts, ["Fish & chips", "A ", "bold", " move", "!", "1 < 2"]);
+}
+
+#[test]
+fn serialize_document() {
+    let document = parser::parse(DOCUMENT);
+    let html = document.outer_html();
+
+    assert_eq!(
+        html,
+        "<body><p>Fish &amp; chips</p><p>A <b>bold<i> move</i></b>!\
+         <video crossorigin=\"true\" src=\"a.mp4\"><img src=\"poster.png\"></video></p>\
+         <p class=\"note\">1 &lt; 2</p></body>",
+    );
+
+    //  Serializing is the inverse of parsing.
+    assert_eq!(parser::parse(&html).outer_html(), html);
+
+    let body = document.first_child().expect("body");
+    assert_eq!(document.inner_html(), html);
+    assert!(body.inner_html().starts_with("<p>Fish"));
+
+    let script = parser::parse("<script>if (a < b && c) {}</script><title>1 &lt; 2</title>");
+    assert_eq!(script.outer_html(), "<script>if (a < b && c) {}</script><title>1 &lt; 2</title>");
+}
+
+#[test]
+fn query_document() {
+    let document = parser::parse(
+        "<div id=main class='page wide'>\
+            <ul><li class=item>one<li class='item last'><a href=x>two</a></ul>\
+            <p>text <a href=y>link</a></p>\
+        </div>\
+        <div><video src=v.mp4></video></div>"
+    );
+
+    let tags = |selectors: &str| -> Vec<String> {
+        let elements = document.query_selector_all(selectors).expect("valid selectors");
+        elements.iter().map(|e| e.as_trait().tag_name().to_string()).collect()
+    };
+
+    assert_eq!(tags("li"), ["li", "li"]);
+    assert_eq!(tags("#main > ul > .item.last a"), ["a"]);
+    assert_eq!(tags("div a"), ["a", "a"]);
+    assert_eq!(tags("div > a"), Vec::<String>::new());
+    assert_eq!(tags(".page [href=y]"), ["a"]);
+    assert_eq!(tags("li:first-child, video[src]"), ["li", "video"]);
+    assert_eq!(tags("div:first-child > *"), ["ul", "p"]);
+    assert_eq!(tags("*").len(), 9);
+
+    let link = document.query_selector("p a").expect("valid selectors").expect("a link");
+    assert_eq!(link.as_struct().get_attribute("href").as_deref(), Some("y"));
+    assert_eq!(link.matches("#main a:first-child"), Ok(true));
+    assert_eq!(link.matches("ul a"), Ok(false));
+
+    let ul = document.query_selector("ul").expect("valid selectors").expect("a list");
+    assert_eq!(ul.query_selector_all("a").map(|a| a.len()), Ok(1));
+
+    assert_eq!(document.query_selector("div >").err(), Some(SelectorError { position: 5 }));
+    assert_eq!(document.query_selector("p:hover").err(), Some(SelectorError { position: 7 }));
+}
+
+#[test]
+fn dispatch_events() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let document = parser::parse("<div><p><video></video></p></div>");
+    let div = document.query_selector("div").expect("valid selectors").expect("a div");
+    let video: RcDyn<dyn Element, HTMLVideoElement> =
+        document.descendants_of().next().expect("a video element");
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let logger = |name: &'static str| -> Listener {
+        let log = log.clone();
+        Rc::new(move |event: &Event| log.borrow_mut().push(format!("{}:{:?}", name, event.phase())))
+    };
+
+    let capture = logger("div");
+    div.add_event_listener("click", capture.clone(), true);
+    div.add_event_listener("click", logger("div"), false);
+    document.add_event_listener("click", logger("document"), false);
+    video.add_event_listener("click", logger("video"), false);
+
+    //  The default action of the video toggles its playback.
+    assert!(video.dispatch_event(&Event::new("click", true, true)));
+    assert!(!video.as_struct().media().paused.get());
+    assert_eq!(
+        *log.borrow(),
+        ["div:Capturing", "video:AtTarget", "div:Bubbling", "document:Bubbling"],
+    );
+
+    //  Stopping the propagation, and preventing the default action.
+    log.borrow_mut().clear();
+    div.remove_event_listener("click", &capture, true);
+    div.add_event_listener("click", Rc::new(|event: &Event| { event.stop_propagation(); event.prevent_default(); }), false);
+
+    assert!(!video.dispatch_event(&Event::new("click", true, true)));
+    assert!(!video.as_struct().media().paused.get());
+    assert_eq!(*log.borrow(), ["video:AtTarget", "div:Bubbling"]);
+
+    //  Events which do not bubble only reach the capturing listeners of the ancestors.
+    log.borrow_mut().clear();
+    video.dispatch_event(&Event::new("click", false, false));
+    assert!(video.as_struct().media().paused.get());
+    assert_eq!(*log.borrow(), ["video:AtTarget"]);
+}
+
+#[test]
+fn dispatch_event_twice() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let document = parser::parse("<div><video></video></div>");
+    let div = document.query_selector("div").expect("valid selectors").expect("a div");
+    let video: RcDyn<dyn Element, HTMLVideoElement> =
+        document.descendants_of().next().expect("a video element");
+
+    let reached = Rc::new(Cell::new(0));
+
+    {
+        let reached = reached.clone();
+        div.add_event_listener("click", Rc::new(move |event: &Event| {
+            reached.set(reached.get() + 1);
+            event.stop_propagation();
+            event.prevent_default();
+        }), false);
+    }
+
+    //  The propagation stopped, and the default prevented, by the first dispatch do not
+    //  carry over to the second.
+    let event = Event::new("click", true, true);
+
+    assert!(!video.dispatch_event(&event));
+    assert!(!video.dispatch_event(&event));
+    assert_eq!(reached.get(), 2);
+    assert_eq!(event.phase(), Phase::None);
+    assert!(event.current_target().is_none());
+    assert!(video.as_struct().media().paused.get());
+}
+
+#[test]
+fn observe_mutations() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let document = parser::parse("<ul id=list><li>one</li><li>two</li></ul><p title=old>text</p>");
+    let list = document.query_selector("ul").expect("valid selectors").expect("a list");
+    let p = document.query_selector("p").expect("valid selectors").expect("a paragraph");
+
+    let delivered = Rc::new(Cell::new(0));
+
+    let observer = {
+        let delivered = delivered.clone();
+        MutationObserver::new(move |records| delivered.set(delivered.get() + records.len()))
+    };
+
+    observer.observe(&document, ObserverOptions { child_list: true, subtree: true, ..Default::default() });
+    observer.observe(&p, ObserverOptions {
+        attributes: true,
+        character_data: true,
+        subtree: true,
+        attribute_filter: Some(vec!("title".to_string())),
+        ..Default::default()
+    });
+
+    //  Child list changes, anywhere in the document.
+    let first = list.first_child().expect("an item");
+    list.remove_child(&first).expect("remove the first item");
+    list.append_child(first.clone()).expect("append it back");
+
+    //  Attribute changes, with their old value, restricted to `title`.
+    ElementData::set_attribute(&p, "title", "new");
+    ElementData::set_attribute(&p, "lang", "en");
+    ElementData::set_attribute(&list, "title", "ignored");
+
+    //  Text changes, within `p`.
+    let text: ClassText = p.first_child().expect("a text").down_cast().expect("a text node");
+    TextNode::set_text(&text, "changed");
+
+    //  Nothing is delivered until flushed.
+    assert_eq!(delivered.get(), 0);
+
+    let records = observer.take_records();
+    let kinds: Vec<_> = records.iter().map(|r| r.kind).collect();
+    assert_eq!(
+        kinds,
+        [MutationKind::ChildList, MutationKind::ChildList, MutationKind::Attributes, MutationKind::CharacterData],
+    );
+
+    assert!(RcDyn::ptr_eq(&records[0].target, &list) && RcDyn::ptr_eq(&records[0].removed_nodes[0], &first));
+    assert!(records[0].previous_sibling.is_none() && records[0].next_sibling.is_some());
+    assert!(RcDyn::ptr_eq(&records[1].added_nodes[0], &first) && records[1].next_sibling.is_none());
+    assert_eq!(records[2].attribute_name.as_deref(), Some("title"));
+    assert_eq!(records[2].old_value.as_deref(), Some("old"));
+    assert_eq!(records[3].old_value.as_deref(), Some("text"));
+
+    //  A replacement is a single record, besides the removal of the node from its former
+    //  parent.
+    let item = list.first_child().expect("an item");
+    list.replace_child(text.as_node(), &item).expect("replace an item");
+
+    let records = observer.take_records();
+    assert_eq!(records.len(), 2);
+    assert!(RcDyn::ptr_eq(&records[0].target, &p));
+    assert!(RcDyn::ptr_eq(&records[1].added_nodes[0], &text) && RcDyn::ptr_eq(&records[1].removed_nodes[0], &item));
+
+    ElementData::set_attribute(&p, "title", "newer");
+    assert_eq!(observer.flush(), 1);
+    assert_eq!(delivered.get(), 1);
+
+    observer.disconnect();
+    list.remove_child(&text.as_node()).expect("remove the text");
+    assert_eq!(observer.flush(), 0);
+}
+
+#[test]
+fn reflect_attributes() {
+    let document = parser::parse("<img src=' a.png ' width=640 height=huge><video autoplay crossorigin=USE-CREDENTIALS>");
+
+    let image: RcDyn<dyn Element, HTMLImageElement> = document.descendants_of().next().expect("an image");
+    let video: RcDyn<dyn Element, HTMLVideoElement> = document.descendants_of().next().expect("a video");
+
+    assert_eq!(image.as_struct().src.get().as_deref(), Some("a.png"));
+    assert_eq!((image.as_struct().width.get(), image.as_struct().height.get()), (640, 0));
+    assert!(video.as_struct().media().autoplay.get());
+    assert_eq!(video.as_struct().media().cross_origin.get(), "use-credentials");
+
+    //  Attribute names are case-insensitive.
+    let data = video.as_element();
+    assert!(data.as_struct().has_attribute("AutoPlay"));
+    assert_eq!(data.as_struct().get_attribute("CROSSORIGIN").as_deref(), Some("USE-CREDENTIALS"));
+
+    //  Removing an attribute resets its reflection.
+    assert_eq!(ElementData::remove_attribute(&video, "crossorigin").as_deref(), Some("USE-CREDENTIALS"));
+    assert_eq!(ElementData::remove_attribute(&video, "crossorigin"), None);
+    assert_eq!(video.as_struct().media().cross_origin.get(), "");
+
+    ElementData::toggle_attribute(&video, "autoplay", false);
+    assert!(!video.as_struct().media().autoplay.get() && !data.as_struct().has_attribute("autoplay"));
+
+    ElementData::toggle_attribute(&video, "autoplay", true);
+    assert!(video.as_struct().media().autoplay.get());
+
+    //  Unreflected attributes are merely stored.
+    ElementData::set_attribute(&image, "alt", "An image");
+    assert_eq!(image.as_element().as_struct().attributes().len(), 4);
+    ElementData::remove_attribute(&image, "width");
+    assert_eq!(image.as_struct().width.get(), 0);
+
+    //  Setting a typed field writes its attribute, the field following.
+    ElementData::set_reflected(&image, "width", 320);
+    assert_eq!(image.as_struct().width.get(), 320);
+    assert_eq!(image.as_element().as_struct().get_attribute("width").as_deref(), Some("320"));
+
+    ElementData::set_reflected(&image, "src", None::<&str>);
+    assert!(image.as_struct().src.get().is_none() && !image.as_element().as_struct().has_attribute("src"));
+
+    ElementData::set_reflected(&video, "crossorigin", "anonymous");
+    assert_eq!(video.as_struct().media().cross_origin.get(), "anonymous");
+    assert_eq!(data.as_struct().get_attribute("crossorigin").as_deref(), Some("anonymous"));
+}
+
+#[test]
+fn html_classes() {
+    let document = parser::parse("<form action=/search method=POST>\
+        <input name=q value='dom trees' maxlength=3><input type=checkbox name=safe>\
+        <input type=hidden name=page value=2><input type=submit name=go>\
+        <select name=sort><option>relevance<option value=new selected>newest</select>\
+        </form>\
+        <audio src=a.ogg></audio><video src=v.webm poster=p.png></video>\
+        <table><tfoot><tr><td>total</tfoot><thead><tr><th colspan=2>head</thead>\
+        <tbody><tr><td>1<td rowspan=3>2</tbody></table>");
+
+    //  Each element is created with the class registered for its tag.
+    let form: ClassForm = document.descendants_of().next().expect("a form");
+    assert_eq!(form.as_struct().action.get().as_deref(), Some("/search"));
+    assert_eq!(form.as_struct().method.get(), "post");
+
+    //  Inputs of different types are instances of different classes, all form controls.
+    let controls: Vec<ClassFormControl> = form.descendants_of().collect();
+    assert_eq!(controls.len(), 5);
+
+    let text: RcDyn<dyn Element, HTMLTextInputElement> = form.descendants_of().next().expect("a text input");
+    assert_eq!(text.as_struct().max_length.get(), 3);
+
+    let checkbox: RcDyn<dyn Element, HTMLCheckboxInputElement> = form.descendants_of().next().expect("a checkbox");
+    assert!(!checkbox.as_struct().checked());
+
+    assert_eq!(HTMLFormElement::entries(&form), [
+        ("q".to_string(), "dom trees".to_string()),
+        ("page".to_string(), "2".to_string()),
+        ("sort".to_string(), "new".to_string()),
+    ]);
+
+    //  Clicking a checkbox checks it, and a checked checkbox is submitted with its form.
+    checkbox.dispatch_event(&Event::new("click", true, true));
+    assert!(checkbox.as_struct().checked());
+    assert_eq!(HTMLFormElement::entries(&form).len(), 4);
+
+    //  Typed setters write their attribute back.
+    HTMLCheckboxInputElement::set_checked(&checkbox, false);
+    assert!(!checkbox.as_struct().checked() && !checkbox.as_element().as_struct().has_attribute("checked"));
+    HTMLCheckboxInputElement::set_checked(&checkbox, true);
+    assert!(checkbox.as_struct().checked() && checkbox.as_element().as_struct().has_attribute("checked"));
+
+    HTMLInputElementData::set_value(&text, "dom");
+    assert_eq!(text.as_element().as_struct().get_attribute("value").as_deref(), Some("dom"));
+    assert_eq!(HTMLFormElement::entries(&form)[0], ("q".to_string(), "dom".to_string()));
+
+    let select: RcDyn<dyn Element, HTMLSelectElement> = form.descendants_of().next().expect("a select");
+    assert_eq!(select.as_struct().selected_index(), Some(1));
+    select.as_struct().select(0);
+    assert_eq!(select.as_struct().value(), "relevance");
+    assert!(!select.as_struct().options()[1].as_struct().selected());
+    assert!(select.as_struct().options()[0].as_element().as_struct().has_attribute("selected"));
+    assert!(!select.as_struct().options()[1].as_element().as_struct().has_attribute("selected"));
+
+    //  Audio and video share HTMLMediaElementData, and are played through MediaElement.
+    let media: Vec<ClassMedia> = document.descendants_of().collect();
+    assert_eq!(media.len(), 2);
+    assert!(media.iter().all(|m| m.as_struct().paused.get()));
+    assert!(!media[0].as_trait().can_play_type("video/webm") && media[1].as_trait().can_play_type("video/webm"));
+
+    media[1].as_trait().play();
+    let video: RcDyn<dyn Element, HTMLVideoElement> = document.descendants_of().next().expect("a video");
+    assert!(!video.as_struct().media().paused.get());
+    assert_eq!(video.as_struct().poster.get().as_deref(), Some("p.png"));
+
+    video.dispatch_event(&Event::new("click", true, true));
+    assert!(video.as_struct().media().paused.get());
+
+    //  Rows are listed head first, foot last, whatever their order in the markup.
+    let table: ClassTable = document.descendants_of().next().expect("a table");
+    let rows = table.as_struct().rows();
+    let first_cells: Vec<_> = rows.iter().map(|r| r.text_content()).collect();
+    assert_eq!(first_cells, ["head", "12", "total"]);
+
+    let cells: Vec<ClassTableCell> = rows[1].as_struct().cells();
+    assert_eq!(cells.len(), 2);
+    assert_eq!((cells[1].as_struct().col_span.get(), cells[1].as_struct().row_span.get()), (1, 3));
+    assert_eq!(rows[0].as_struct().cells()[0].as_struct().col_span.get(), 2);
+
+    let sections: Vec<_> = table.children_of::<ClassTableSection>().map(|s| s.as_trait().tag_name().to_string()).collect();
+    assert_eq!(sections, ["tfoot", "thead", "tbody"]);
+    assert_eq!(table.descendants_of::<ClassTableRow>().count(), 3);
+
+    //  Radio buttons are plain inputs, not checkboxes, and are not submitted.
+    let form = parser::parse("<form><input type=radio name=size value=small checked></form>");
+    let form: ClassForm = form.descendants_of().next().expect("a form");
+    assert!(form.descendants_of::<RcDyn<dyn Element, HTMLCheckboxInputElement>>().next().is_none());
+    assert_eq!(form.descendants_of::<ClassFormControl>().count(), 1);
+    assert!(HTMLFormElement::entries(&form).is_empty());
+}
+
+//
+//  KLUDGE: should be automatically implemented by the compiler.
+//
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct NodeData impl Node;
+    #[derive(PolyStruct)] struct TextNode impl Node;
+    #[derive(PolyStruct)] struct DocumentData impl Node;
+    #[derive(PolyStruct)] struct ElementData impl Element;
+    #[derive(PolyStruct)] struct HTMLAnchorElement impl Element;
+    #[derive(PolyStruct)] struct HTMLImageElement impl Element;
+
+    #[derive(PolyStruct)] struct HTMLMediaElementData impl MediaElement;
+    #[derive(PolyStruct)] struct HTMLAudioElement impl MediaElement;
+    #[derive(PolyStruct)] struct HTMLVideoElement impl MediaElement;
+
+    #[derive(PolyStruct)] struct HTMLFormElement impl Element;
+    #[derive(PolyStruct)] struct HTMLInputElementData impl FormControl;
+    #[derive(PolyStruct)] struct HTMLTextInputElement impl FormControl;
+    #[derive(PolyStruct)] struct HTMLCheckboxInputElement impl FormControl;
+    #[derive(PolyStruct)] struct HTMLSelectElement impl FormControl;
+    #[derive(PolyStruct)] struct HTMLOptionElement impl Element;
+
+    #[derive(PolyStruct)] struct HTMLTableElement impl Element;
+    #[derive(PolyStruct)] struct HTMLTableSectionElement impl Element;
+    #[derive(PolyStruct)] struct HTMLTableRowElement impl Element;
+    #[derive(PolyStruct)] struct HTMLTableCellElement impl Element;
+}
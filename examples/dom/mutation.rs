@@ -0,0 +1,204 @@
+//
+//  Mutation observers
+//
+//  Each node holds the registrations of the observers observing it. A mutation queues a
+//  record with every observer registered on the target, or on one of its ancestors with
+//  `subtree`, whose options select it; each observer receives a given mutation once.
+//
+//  Records are only delivered when the observer is flushed, or taken from it.
+//
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use poly::RcDyn;
+
+use crate::node::{ClassNode, Tree, WeakClassNode};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MutationKind {
+    Attributes,
+    ChildList,
+    CharacterData,
+}
+
+#[derive(Clone)]
+pub struct MutationRecord {
+    pub kind: MutationKind,
+    pub target: ClassNode,
+    //  For Attributes.
+    pub attribute_name: Option<String>,
+    //  For Attributes and CharacterData; None if the attribute was absent.
+    pub old_value: Option<String>,
+    //  For ChildList.
+    pub added_nodes: Vec<ClassNode>,
+    pub removed_nodes: Vec<ClassNode>,
+    pub previous_sibling: Option<ClassNode>,
+    pub next_sibling: Option<ClassNode>,
+}
+
+impl MutationRecord {
+    fn new(kind: MutationKind, target: &ClassNode) -> MutationRecord {
+        MutationRecord {
+            kind,
+            target: target.clone(),
+            attribute_name: None,
+            old_value: None,
+            added_nodes: Vec::new(),
+            removed_nodes: Vec::new(),
+            previous_sibling: None,
+            next_sibling: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ObserverOptions {
+    pub child_list: bool,
+    pub attributes: bool,
+    pub character_data: bool,
+    //  Also observe the descendants of the node.
+    pub subtree: bool,
+    //  Only observe these attributes, if any.
+    pub attribute_filter: Option<Vec<String>>,
+}
+
+impl ObserverOptions {
+    fn selects(&self, record: &MutationRecord) -> bool {
+        match record.kind {
+        MutationKind::ChildList => self.child_list,
+        MutationKind::CharacterData => self.character_data,
+        MutationKind::Attributes => self.attributes && match (&self.attribute_filter, &record.attribute_name) {
+            (Some(filter), Some(name)) => filter.iter().any(|f| f == name),
+            _ => true,
+        },
+        }
+    }
+}
+
+//
+//  MutationObserver
+//
+pub type Callback = Box<dyn Fn(&[MutationRecord])>;
+
+#[derive(Clone)]
+pub struct MutationObserver(Rc<Inner>);
+
+struct Inner {
+    callback: Callback,
+    records: RefCell<Vec<MutationRecord>>,
+    nodes: RefCell<Vec<WeakClassNode>>,
+}
+
+impl MutationObserver {
+    pub fn new<F: Fn(&[MutationRecord]) + 'static>(callback: F) -> MutationObserver {
+        MutationObserver(Rc::new(Inner {
+            callback: Box::new(callback),
+            records: RefCell::default(),
+            nodes: RefCell::default(),
+        }))
+    }
+
+    //  Observing a node again replaces the previous options.
+    pub fn observe<N: Tree + ?Sized>(&self, node: &N, options: ObserverOptions) {
+        let node = node.as_node();
+        let mut entries = node.as_struct().observers().entries.borrow_mut();
+
+        match entries.iter_mut().find(|e| is_same(&e.observer, &self.0)) {
+        Some(entry) => entry.options = options,
+        None => {
+            entries.push(Entry { observer: Rc::downgrade(&self.0), options });
+            self.0.nodes.borrow_mut().push(RcDyn::downgrade(&node));
+        },
+        }
+    }
+
+    //  Stops observing all nodes, discarding pending records.
+    pub fn disconnect(&self) {
+        for node in self.0.nodes.take().iter().filter_map(|n| n.upgrade()) {
+            node.as_struct().observers().entries.borrow_mut().retain(|e| !is_same(&e.observer, &self.0));
+        }
+
+        self.0.records.borrow_mut().clear();
+    }
+
+    pub fn take_records(&self) -> Vec<MutationRecord> { self.0.records.take() }
+
+    //  Delivers the pending records, if any, to the callback; returns their number.
+    pub fn flush(&self) -> usize {
+        let records = self.take_records();
+
+        if !records.is_empty() { (self.0.callback)(&records); }
+
+        records.len()
+    }
+}
+
+fn is_same(weak: &Weak<Inner>, strong: &Rc<Inner>) -> bool {
+    std::ptr::eq(weak.as_ptr(), Rc::as_ptr(strong))
+}
+
+//  The registrations on a node, held by NodeData.
+#[derive(Default)]
+pub struct Observers {
+    entries: RefCell<Vec<Entry>>,
+}
+
+struct Entry {
+    observer: Weak<Inner>,
+    options: ObserverOptions,
+}
+
+//
+//  Queueing
+//
+fn queue(record: MutationRecord) {
+    let mut notified: Vec<Rc<Inner>> = Vec::new();
+
+    let inclusive_ancestors = Some(record.target.clone()).into_iter().chain(record.target.ancestors());
+
+    for (depth, node) in inclusive_ancestors.enumerate() {
+        for entry in node.as_struct().observers().entries.borrow().iter() {
+            if depth > 0 && !entry.options.subtree { continue; }
+            if !entry.options.selects(&record) { continue; }
+
+            let observer = match entry.observer.upgrade() {
+            Some(observer) => observer,
+            None => continue,
+            };
+
+            if notified.iter().any(|n| Rc::ptr_eq(n, &observer)) { continue; }
+
+            observer.records.borrow_mut().push(record.clone());
+            notified.push(observer);
+        }
+    }
+}
+
+pub fn queue_attribute(target: &ClassNode, name: &str, old_value: Option<String>) {
+    let mut record = MutationRecord::new(MutationKind::Attributes, target);
+    record.attribute_name = Some(name.to_string());
+    record.old_value = old_value;
+    queue(record);
+}
+
+pub fn queue_character_data(target: &ClassNode, old_value: String) {
+    let mut record = MutationRecord::new(MutationKind::CharacterData, target);
+    record.old_value = Some(old_value);
+    queue(record);
+}
+
+pub fn queue_child_list(
+    target: &ClassNode,
+    added_nodes: Vec<ClassNode>,
+    removed_nodes: Vec<ClassNode>,
+    previous_sibling: Option<ClassNode>,
+    next_sibling: Option<ClassNode>,
+)
+{
+    let mut record = MutationRecord::new(MutationKind::ChildList, target);
+    record.added_nodes = added_nodes;
+    record.removed_nodes = removed_nodes;
+    record.previous_sibling = previous_sibling;
+    record.next_sibling = next_sibling;
+    queue(record);
+}
@@ -0,0 +1,485 @@
+//
+//  Node: the tree itself
+//
+//  Nodes are shared, through RcDyn handles; a node owns its first child and its next
+//  sibling, and refers weakly to its parent, last child and previous sibling.
+//
+use std::cell::{Ref, RefCell, RefMut};
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+
+use poly::{poly_trait, PolyStruct, RawClone};
+use poly::{DownCast, RcDyn, TraitExtendTrait, UniqueExtendStruct, UpCast, WeakRcDyn};
+
+use crate::events::{Event, Listeners};
+use crate::mutation::{self, Observers};
+
+//
+//  ClassNode
+//
+pub type ClassNode = RcDyn<dyn Node, NodeData>;
+pub type WeakClassNode = WeakRcDyn<dyn Node, NodeData>;
+
+#[poly_trait]
+pub trait Node: RawClone {
+    //  Whether this node may have children.
+    fn accepts_children(&self) -> bool { true }
+
+    //  Performs the default action of `event`, dispatched to this node or one of its
+    //  descendants; returns whether it was handled, which ends the search.
+    fn default_action(&self, _event: &Event) -> bool { false }
+}
+
+#[repr(C)]
+#[derive(Default, PolyStruct)]
+pub struct NodeData {
+    links: RefCell<Links>,
+    listeners: Listeners,
+    observers: Observers,
+}
+
+#[derive(Default)]
+struct Links {
+    parent: Option<WeakClassNode>,
+    first_child: Option<ClassNode>,
+    last_child: Option<WeakClassNode>,
+    previous_sibling: Option<WeakClassNode>,
+    next_sibling: Option<ClassNode>,
+}
+
+impl NodeData {
+    pub fn parent(&self) -> Option<ClassNode> { upgrade(&self.links().parent) }
+
+    pub fn first_child(&self) -> Option<ClassNode> { self.links().first_child.clone() }
+
+    pub fn last_child(&self) -> Option<ClassNode> { upgrade(&self.links().last_child) }
+
+    pub fn previous_sibling(&self) -> Option<ClassNode> { upgrade(&self.links().previous_sibling) }
+
+    pub fn next_sibling(&self) -> Option<ClassNode> { self.links().next_sibling.clone() }
+
+    //  The concatenated text of the descendants.
+    pub fn text_content(&self) -> String {
+        let mut content = String::new();
+        let mut child = self.first_child();
+
+        while let Some(node) = child {
+            let text: Option<ClassText> = node.clone().down_cast().ok();
+
+            match text {
+            Some(text) => content.push_str(&text.as_struct().text()),
+            None => content.push_str(&node.as_struct().text_content()),
+            }
+
+            child = node.next_sibling();
+        }
+
+        content
+    }
+
+    pub fn listeners(&self) -> &Listeners { &self.listeners }
+
+    pub fn observers(&self) -> &Observers { &self.observers }
+
+    fn links(&self) -> Ref<'_, Links> { self.links.borrow() }
+
+    fn links_mut(&self) -> RefMut<'_, Links> { self.links.borrow_mut() }
+}
+
+impl Node for NodeData {}
+
+//  A clone is detached from the tree, and has neither listeners nor observers, as with
+//  cloneNode.
+impl Clone for NodeData {
+    fn clone(&self) -> NodeData { NodeData::default() }
+}
+
+impl fmt::Debug for NodeData {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let links = self.links();
+        write!(
+            formatter,
+            "NodeData {{ has_parent: {}, has_children: {} }}",
+            links.parent.is_some(),
+            links.first_child.is_some(),
+        )
+    }
+}
+
+//  Siblings are released one at a time, rather than recursively, so that long lists of
+//  children do not overflow the stack.
+impl Drop for Links {
+    fn drop(&mut self) {
+        let mut next = self.first_child.take();
+
+        while let Some(node) = next {
+            next = node.as_struct().links_mut().next_sibling.take();
+        }
+    }
+}
+
+fn upgrade(weak: &Option<WeakClassNode>) -> Option<ClassNode> {
+    weak.as_ref().and_then(WeakRcDyn::upgrade)
+}
+
+//
+//  ClassText
+//
+pub type ClassText = RcDyn<dyn Node, TextNode>;
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+pub struct TextNode {
+    #[parent]
+    _first_parent: NodeData,
+    text: RefCell<String>,
+}
+
+impl TextNode {
+    pub fn new(text: &str) -> TextNode {
+        TextNode { _first_parent: NodeData::default(), text: RefCell::new(text.to_string()) }
+    }
+
+    pub fn text(&self) -> String { self.text.borrow().clone() }
+
+    //  Through the handle, so that observers of the node are notified.
+    pub fn set_text(node: &ClassText, text: &str) {
+        let old = node.as_struct().text.replace(text.to_string());
+        mutation::queue_character_data(&node.as_node(), old);
+    }
+}
+
+impl Node for TextNode {
+    fn accepts_children(&self) -> bool { false }
+}
+
+//
+//  ClassDocument
+//
+pub type ClassDocument = RcDyn<dyn Node, DocumentData>;
+
+#[repr(C)]
+#[derive(Clone, Debug, Default, PolyStruct)]
+pub struct DocumentData {
+    #[parent]
+    _first_parent: NodeData,
+}
+
+impl Node for DocumentData {}
+
+//
+//  Tree manipulation
+//
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DomError {
+    //  The operation would yield an incorrect tree: a node inserted within itself, or in
+    //  a node which does not accept children.
+    HierarchyRequest,
+    //  The node is not a child of this node.
+    NotFound,
+}
+
+impl fmt::Display for DomError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+        DomError::HierarchyRequest => write!(formatter, "the operation would yield an incorrect node tree"),
+        DomError::NotFound => write!(formatter, "the node is not a child of this node"),
+        }
+    }
+}
+
+impl error::Error for DomError {}
+
+//  Implemented for any handle on a node, whichever its class.
+pub trait Tree {
+    fn as_node(&self) -> ClassNode;
+
+    fn parent(&self) -> Option<ClassNode> { self.as_node().as_struct().parent() }
+
+    fn first_child(&self) -> Option<ClassNode> { self.as_node().as_struct().first_child() }
+
+    fn last_child(&self) -> Option<ClassNode> { self.as_node().as_struct().last_child() }
+
+    fn previous_sibling(&self) -> Option<ClassNode> { self.as_node().as_struct().previous_sibling() }
+
+    fn next_sibling(&self) -> Option<ClassNode> { self.as_node().as_struct().next_sibling() }
+
+    fn text_content(&self) -> String { self.as_node().as_struct().text_content() }
+
+    //  The children of this node, in order.
+    fn children(&self) -> Siblings { Siblings { next: self.first_child() } }
+
+    //  The descendants of this node, in pre-order, excluding the node itself.
+    fn descendants(&self) -> Descendants {
+        let root = self.as_node();
+        Descendants { next: root.first_child(), root }
+    }
+
+    //  The ancestors of this node, from its parent up to the root.
+    fn ancestors(&self) -> Ancestors { Ancestors { next: self.parent() } }
+
+    //  The siblings following this node, in order.
+    fn following_siblings(&self) -> Siblings { Siblings { next: self.next_sibling() } }
+
+    //  As `children`, only yielding the nodes of class C.
+    fn children_of<C>(&self) -> OfClass<Siblings, C>
+        where ClassNode: DownCast<C>
+    {
+        OfClass::new(self.children())
+    }
+
+    //  As `descendants`, only yielding the nodes of class C.
+    fn descendants_of<C>(&self) -> OfClass<Descendants, C>
+        where ClassNode: DownCast<C>
+    {
+        OfClass::new(self.descendants())
+    }
+
+    //  As `ancestors`, only yielding the nodes of class C.
+    fn ancestors_of<C>(&self) -> OfClass<Ancestors, C>
+        where ClassNode: DownCast<C>
+    {
+        OfClass::new(self.ancestors())
+    }
+
+    //  As `following_siblings`, only yielding the nodes of class C.
+    fn following_siblings_of<C>(&self) -> OfClass<Siblings, C>
+        where ClassNode: DownCast<C>
+    {
+        OfClass::new(self.following_siblings())
+    }
+
+    //  Appends `child`, removing it from its current parent first; returns `child`.
+    fn append_child(&self, child: ClassNode) -> Result<ClassNode, DomError> {
+        self.insert_before(child, None)
+    }
+
+    //  Inserts `child` before `reference`, or last if None; returns `child`.
+    fn insert_before(&self, child: ClassNode, reference: Option<&ClassNode>) -> Result<ClassNode, DomError> {
+        let parent = self.as_node();
+
+        check_insertion(&parent, &child)?;
+        if let Some(reference) = reference { check_child(&parent, reference)?; }
+
+        //  Inserting a node before itself is inserting it before its next sibling.
+        let reference = match reference {
+        Some(r) if RcDyn::ptr_eq(r, &child) => child.next_sibling(),
+        r => r.cloned(),
+        };
+
+        remove(&child);
+        insert(&parent, &child, reference);
+        queue_insertion(&parent, &child, Vec::new());
+
+        Ok(child)
+    }
+
+    //  Removes `child`, which is returned.
+    fn remove_child(&self, child: &ClassNode) -> Result<ClassNode, DomError> {
+        check_child(&self.as_node(), child)?;
+
+        remove(child);
+
+        Ok(child.clone())
+    }
+
+    //  Replaces `old` by `child`; returns `old`.
+    fn replace_child(&self, child: ClassNode, old: &ClassNode) -> Result<ClassNode, DomError> {
+        let parent = self.as_node();
+
+        check_child(&parent, old)?;
+        check_insertion(&parent, &child)?;
+
+        if RcDyn::ptr_eq(&child, old) { return Ok(child); }
+
+        let reference = match old.next_sibling() {
+        Some(next) if RcDyn::ptr_eq(&next, &child) => child.next_sibling(),
+        next => next,
+        };
+
+        remove(&child);
+        detach(old);
+        insert(&parent, &child, reference);
+        queue_insertion(&parent, &child, vec!(old.clone()));
+
+        Ok(old.clone())
+    }
+}
+
+impl<T: ?Sized, S> Tree for RcDyn<T, S>
+    where T: TraitExtendTrait<dyn Node> + 'static,
+          S: UniqueExtendStruct<NodeData> + 'static,
+{
+    fn as_node(&self) -> ClassNode { self.clone().up_cast() }
+}
+
+//
+//  Traversal
+//
+//  The iterators hold on to the next node to yield, rather than borrowing the tree, hence
+//  the tree may be modified while iterating; a removed node simply ends its own traversal.
+//
+pub struct Siblings {
+    next: Option<ClassNode>,
+}
+
+impl Iterator for Siblings {
+    type Item = ClassNode;
+
+    fn next(&mut self) -> Option<ClassNode> {
+        let node = self.next.take()?;
+        self.next = node.next_sibling();
+        Some(node)
+    }
+}
+
+pub struct Descendants {
+    root: ClassNode,
+    next: Option<ClassNode>,
+}
+
+impl Iterator for Descendants {
+    type Item = ClassNode;
+
+    fn next(&mut self) -> Option<ClassNode> {
+        let node = self.next.take()?;
+
+        //  First child, or else the next sibling of the closest inclusive ancestor which
+        //  has one, without climbing above the root.
+        self.next = node.first_child().or_else(|| {
+            let mut current = node.clone();
+
+            loop {
+                if RcDyn::ptr_eq(&current, &self.root) { return None; }
+                if let Some(next) = current.next_sibling() { return Some(next); }
+                current = current.parent()?;
+            }
+        });
+
+        Some(node)
+    }
+}
+
+pub struct Ancestors {
+    next: Option<ClassNode>,
+}
+
+impl Iterator for Ancestors {
+    type Item = ClassNode;
+
+    fn next(&mut self) -> Option<ClassNode> {
+        let node = self.next.take()?;
+        self.next = node.parent();
+        Some(node)
+    }
+}
+
+//  Filters the nodes of the underlying iterator, down-casting them to C.
+pub struct OfClass<I, C> {
+    inner: I,
+    _marker: PhantomData<fn() -> C>,
+}
+
+impl<I, C> OfClass<I, C> {
+    fn new(inner: I) -> OfClass<I, C> { OfClass { inner, _marker: PhantomData } }
+}
+
+impl<I, C> Iterator for OfClass<I, C>
+    where I: Iterator<Item = ClassNode>,
+          ClassNode: DownCast<C>,
+{
+    type Item = C;
+
+    fn next(&mut self) -> Option<C> {
+        self.inner.find_map(|node| node.down_cast().ok())
+    }
+}
+
+fn is_inclusive_ancestor(ancestor: &ClassNode, node: &ClassNode) -> bool {
+    RcDyn::ptr_eq(node, ancestor) || node.ancestors().any(|a| RcDyn::ptr_eq(&a, ancestor))
+}
+
+fn check_insertion(parent: &ClassNode, child: &ClassNode) -> Result<(), DomError> {
+    if !parent.as_trait().accepts_children() || is_inclusive_ancestor(child, parent) {
+        return Err(DomError::HierarchyRequest);
+    }
+
+    Ok(())
+}
+
+fn check_child(parent: &ClassNode, child: &ClassNode) -> Result<(), DomError> {
+    match child.parent() {
+    Some(p) if RcDyn::ptr_eq(&p, parent) => Ok(()),
+    _ => Err(DomError::NotFound),
+    }
+}
+
+//  Unlinks `node` from its parent and siblings, if any.
+fn detach(node: &ClassNode) {
+    let (parent, previous, next) = {
+        let mut links = node.as_struct().links_mut();
+        let parent = links.parent.take().and_then(|p| p.upgrade());
+        let previous = links.previous_sibling.take().and_then(|p| p.upgrade());
+        (parent, previous, links.next_sibling.take())
+    };
+
+    let weak_previous = previous.as_ref().map(RcDyn::downgrade);
+
+    match (&next, &parent) {
+    (Some(next), _) => next.as_struct().links_mut().previous_sibling = weak_previous,
+    (None, Some(parent)) => parent.as_struct().links_mut().last_child = weak_previous,
+    (None, None) => {}
+    }
+
+    match (&previous, &parent) {
+    (Some(previous), _) => previous.as_struct().links_mut().next_sibling = next,
+    (None, Some(parent)) => parent.as_struct().links_mut().first_child = next,
+    (None, None) => {}
+    }
+}
+
+//  Detaches `node`, notifying the observers of its former parent.
+fn remove(node: &ClassNode) {
+    let parent = match node.parent() {
+    Some(parent) => parent,
+    None => return,
+    };
+
+    let (previous, next) = (node.previous_sibling(), node.next_sibling());
+
+    detach(node);
+
+    mutation::queue_child_list(&parent, Vec::new(), vec!(node.clone()), previous, next);
+}
+
+//  Notifies the observers of `parent` that `child` was inserted, replacing `removed`.
+fn queue_insertion(parent: &ClassNode, child: &ClassNode, removed: Vec<ClassNode>) {
+    let (previous, next) = (child.previous_sibling(), child.next_sibling());
+    mutation::queue_child_list(parent, vec!(child.clone()), removed, previous, next);
+}
+
+//  Links the detached `child` into `parent`, before `reference` or last if None.
+fn insert(parent: &ClassNode, child: &ClassNode, reference: Option<ClassNode>) {
+    let previous = match reference {
+    Some(ref r) => upgrade(&r.as_struct().links().previous_sibling),
+    None => parent.last_child(),
+    };
+
+    {
+        let mut links = child.as_struct().links_mut();
+        links.parent = Some(RcDyn::downgrade(parent));
+        links.previous_sibling = previous.as_ref().map(RcDyn::downgrade);
+        links.next_sibling = reference.clone();
+    }
+
+    match reference {
+    Some(ref r) => r.as_struct().links_mut().previous_sibling = Some(RcDyn::downgrade(child)),
+    None => parent.as_struct().links_mut().last_child = Some(RcDyn::downgrade(child)),
+    }
+
+    match previous {
+    Some(ref p) => p.as_struct().links_mut().next_sibling = Some(child.clone()),
+    None => parent.as_struct().links_mut().first_child = Some(child.clone()),
+    }
+}
@@ -0,0 +1,385 @@
+//
+//  Parser: from markup to a document
+//
+//  A tolerant parser, in two stages:
+//  - the tokeniser splits the markup into tags, text and comments, decoding character
+//    references, and treating anything it cannot make sense of as text.
+//  - the tree builder maintains the stack of open elements, creating each element from
+//    the class registered for its tag, closing elements implicitly where HTML allows it,
+//    and ignoring stray end tags.
+//
+use std::collections::HashMap;
+
+use poly::{Class, RcDyn};
+
+use crate::element::{new_element, ClassElement, ElementData};
+use crate::html;
+use crate::node::{ClassDocument, ClassNode, ClassText, DocumentData, TextNode, Tree};
+
+//
+//  Tag registry
+//
+//  Called with the tag and the attributes, which are set afterwards.
+pub type Constructor = fn(&str, &[(String, String)]) -> ClassElement;
+
+//  Maps tag names to element classes; unregistered tags yield a plain ElementData.
+pub struct TagRegistry {
+    constructors: HashMap<String, Constructor>,
+}
+
+impl TagRegistry {
+    pub fn new() -> TagRegistry { TagRegistry { constructors: HashMap::new() } }
+
+    pub fn register(&mut self, tag: &str, constructor: Constructor) {
+        self.constructors.insert(tag.to_ascii_lowercase(), constructor);
+    }
+
+    pub fn create(&self, tag: &str, attributes: &[(String, String)]) -> ClassElement {
+        match self.constructors.get(tag) {
+        Some(constructor) => constructor(tag, attributes),
+        None => new_element(ElementData::new(tag)),
+        }
+    }
+}
+
+impl Default for TagRegistry {
+    fn default() -> TagRegistry {
+        let mut registry = TagRegistry::new();
+        html::register(&mut registry);
+        registry
+    }
+}
+
+//
+//  Tokeniser
+//
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Token {
+    Doctype(String),
+    StartTag { name: String, attributes: Vec<(String, String)>, self_closing: bool },
+    EndTag { name: String },
+    Text(String),
+    Comment(String),
+}
+
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    position: usize,
+    //  The element whose content is raw text, up until its end tag.
+    raw_text: Option<String>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Tokenizer<'a> {
+        Tokenizer { input, position: 0, raw_text: None }
+    }
+
+    fn rest(&self) -> &'a str { &self.input[self.position..] }
+
+    fn next_raw_text(&mut self, name: String) -> Option<Token> {
+        let rest = self.rest();
+        let end = find_ignore_case(rest, &format!("</{}", name)).unwrap_or(rest.len());
+
+        self.position += end;
+
+        if end == 0 { return self.next(); }
+
+        let text = &rest[..end];
+        Some(Token::Text(if name == "title" || name == "textarea" { decode(text) } else { text.to_string() }))
+    }
+
+    fn next_text(&mut self) -> Token {
+        let rest = self.rest();
+
+        //  A '<' which starts nothing is text.
+        let end = rest.char_indices().skip(1).find(|&(_, c)| c == '<').map_or(rest.len(), |(i, _)| i);
+
+        self.position += end;
+
+        Token::Text(decode(&rest[..end]))
+    }
+
+    //  Parses the markup following '<', returning None if it is not a tag after all.
+    fn next_markup(&mut self) -> Option<Token> {
+        let rest = self.rest();
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let (text, length) = match comment.find("-->") {
+            Some(end) => (&comment[..end], end + 3),
+            None => (comment, comment.len()),
+            };
+            self.position += 4 + length;
+            return Some(Token::Comment(text.to_string()));
+        }
+
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            self.position += end;
+            let text = rest[2..end].trim_end_matches('>');
+            return Some(if rest.starts_with("<!") && starts_with_ignore_case(text, "doctype") {
+                Token::Doctype(text[7..].trim().to_string())
+            } else {
+                Token::Comment(text.to_string())
+            });
+        }
+
+        let (is_end, after) = match rest.strip_prefix("</") {
+        Some(after) => (true, after),
+        None => (false, &rest[1..]),
+        };
+
+        if !after.starts_with(|c: char| c.is_ascii_alphabetic()) { return None; }
+
+        let mut cursor = Cursor { input: after, position: 0 };
+        let name = cursor.take_while(|c| !c.is_whitespace() && c != '/' && c != '>').to_ascii_lowercase();
+
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        let mut self_closing = false;
+
+        loop {
+            cursor.skip_whitespace();
+
+            match cursor.peek() {
+            None => break,
+            Some('>') => { cursor.bump(); break; },
+            Some('/') => { cursor.bump(); self_closing = cursor.peek() == Some('>'); continue; },
+            Some(_) => {},
+            }
+
+            let key = cursor.take_while(|c| !c.is_whitespace() && c != '/' && c != '>' && c != '=');
+            let key = if key.is_empty() { cursor.bump().map(String::from).unwrap_or_default() } else { key };
+
+            cursor.skip_whitespace();
+
+            let value = if cursor.peek() == Some('=') {
+                cursor.bump();
+                cursor.skip_whitespace();
+                decode(&cursor.take_value())
+            } else {
+                String::new()
+            };
+
+            //  The first occurrence of an attribute wins.
+            if !attributes.iter().any(|(k, _)| k.eq_ignore_ascii_case(&key)) {
+                attributes.push((key.to_ascii_lowercase(), value));
+            }
+        }
+
+        self.position += 1 + usize::from(is_end) + cursor.position;
+
+        Some(if is_end {
+            Token::EndTag { name }
+        } else {
+            if !self_closing && is_raw_text(&name) { self.raw_text = Some(name.clone()); }
+            Token::StartTag { name, attributes, self_closing }
+        })
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if let Some(name) = self.raw_text.take() { return self.next_raw_text(name); }
+
+        if self.position >= self.input.len() { return None; }
+
+        if self.rest().starts_with('<') {
+            if let Some(token) = self.next_markup() { return Some(token); }
+        }
+
+        Some(self.next_text())
+    }
+}
+
+struct Cursor<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<char> { self.input[self.position..].chars().next() }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> String {
+        let rest = &self.input[self.position..];
+        let end = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.position += end;
+        rest[..end].to_string()
+    }
+
+    fn skip_whitespace(&mut self) { self.take_while(char::is_whitespace); }
+
+    //  A quoted value, up to its closing quote or the end of input, or an unquoted one.
+    fn take_value(&mut self) -> String {
+        match self.peek() {
+        Some(quote @ ('"' | '\'')) => {
+            self.bump();
+            let value = self.take_while(|c| c != quote);
+            self.bump();
+            value
+        },
+        _ => self.take_while(|c| !c.is_whitespace() && c != '>'),
+        }
+    }
+}
+
+fn is_raw_text(name: &str) -> bool {
+    matches!(name, "script" | "style" | "textarea" | "title")
+}
+
+fn starts_with_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.len() >= needle.len() && haystack.as_bytes()[..needle.len()].eq_ignore_ascii_case(needle.as_bytes())
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    (0..haystack.len())
+        .filter(|&i| haystack.is_char_boundary(i))
+        .find(|&i| starts_with_ignore_case(&haystack[i..], needle))
+}
+
+//  Decodes the character references of `text`; unknown ones are left as is.
+pub fn decode(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest[1..].find(';').filter(|&end| end <= 10).and_then(|end| {
+            let name = &rest[1..end + 1];
+
+            let c = match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => match name.strip_prefix('#') {
+                Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok().and_then(char::from_u32),
+                Some(decimal) => decimal.parse().ok().and_then(char::from_u32),
+                None => None,
+            },
+            };
+
+            c.map(|c| (c, end + 2))
+        });
+
+        match decoded {
+        Some((c, length)) => { result.push(c); rest = &rest[length..]; },
+        None => { result.push('&'); rest = &rest[1..]; },
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+//
+//  Tree builder
+//
+//  Elements which never have content.
+pub fn is_void(name: &str) -> bool {
+    matches!(
+        name,
+        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input"
+            | "link" | "meta" | "param" | "source" | "track" | "wbr"
+    )
+}
+
+//  Whether opening `name` implicitly closes the open element `open`.
+fn closes(name: &str, open: &str) -> bool {
+    match open {
+    "p" => matches!(
+        name,
+        "address" | "article" | "aside" | "blockquote" | "div" | "dl" | "fieldset" | "footer"
+            | "form" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "header" | "hr" | "li" | "main"
+            | "nav" | "ol" | "p" | "pre" | "section" | "table" | "ul"
+    ),
+    "li" => name == "li",
+    "dt" | "dd" => matches!(name, "dt" | "dd"),
+    "option" => matches!(name, "option" | "optgroup"),
+    "tr" => name == "tr",
+    "td" | "th" => matches!(name, "td" | "th" | "tr"),
+    _ => false,
+    }
+}
+
+pub fn parse(html: &str) -> ClassDocument {
+    parse_with(html, &TagRegistry::default())
+}
+
+pub fn parse_with(html: &str, registry: &TagRegistry) -> ClassDocument {
+    let document: ClassDocument = RcDyn::new(Class::new(DocumentData::default()));
+
+    let mut builder = Builder { registry, open: vec!((document.as_node(), String::new())), text: String::new() };
+
+    for token in Tokenizer::new(html) {
+        builder.process(token);
+    }
+
+    builder.flush_text();
+
+    document
+}
+
+struct Builder<'a> {
+    registry: &'a TagRegistry,
+    //  The document, followed by the open elements, with their tags.
+    open: Vec<(ClassNode, String)>,
+    //  Pending text, so that adjacent text tokens yield a single node.
+    text: String,
+}
+
+impl<'a> Builder<'a> {
+    fn current(&self) -> &ClassNode { &self.open.last().expect("document").0 }
+
+    fn process(&mut self, token: Token) {
+        match token {
+        Token::Text(text) => self.text.push_str(&text),
+        Token::StartTag { name, attributes, self_closing } => {
+            self.flush_text();
+
+            while self.open.len() > 1 && closes(&name, &self.open[self.open.len() - 1].1) {
+                self.open.pop();
+            }
+
+            let element = self.registry.create(&name, &attributes);
+
+            for (key, value) in &attributes {
+                ElementData::set_attribute(&element, key, value);
+            }
+
+            let node = element.as_node();
+            self.current().append_child(node.clone()).expect("elements accept children");
+
+            if !self_closing && !is_void(&name) { self.open.push((node, name)); }
+        },
+        Token::EndTag { name } => {
+            self.flush_text();
+
+            //  Stray end tags are ignored.
+            if let Some(index) = (1..self.open.len()).rev().find(|&i| self.open[i].1 == name) {
+                self.open.truncate(index);
+            }
+        },
+        Token::Doctype(_) | Token::Comment(_) => self.flush_text(),
+        }
+    }
+
+    fn flush_text(&mut self) {
+        if self.text.is_empty() { return; }
+
+        let text: ClassText = RcDyn::new(Class::new(TextNode::new(&self.text)));
+        self.text.clear();
+
+        self.current().append_child(text.as_node()).expect("elements accept children");
+    }
+}
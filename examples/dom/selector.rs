@@ -0,0 +1,293 @@
+//
+//  Selector: a subset of CSS selectors
+//
+//      list     := complex (',' complex)*
+//      complex  := compound ((' ' | '>') compound)*
+//      compound := (type | '*')? ('#' id | '.' class | '[' attr ('=' value)? ']' | ':first-child')*
+//
+//  Only elements are matched: a node is down-cast to ClassElement, then its tag name is
+//  obtained through its v-table, and its attributes from ElementData.
+//
+use std::error;
+use std::fmt;
+
+use poly::DownCast;
+
+use crate::element::{AsElement, ClassElement};
+use crate::node::{ClassNode, Tree};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SelectorList(Vec<Complex>);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Complex {
+    //  The right-most compound, then each combinator with the compound on its left.
+    subject: Compound,
+    ancestors: Vec<(Combinator, Compound)>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Compound {
+    tag: Option<String>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, Option<String>)>,
+    first_child: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SelectorError {
+    //  The byte offset at which parsing failed.
+    pub position: usize,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "invalid selector at offset {}", self.position)
+    }
+}
+
+impl error::Error for SelectorError {}
+
+//
+//  Parsing
+//
+impl SelectorList {
+    pub fn parse(selectors: &str) -> Result<SelectorList, SelectorError> {
+        let mut parser = Parser { input: selectors, position: 0 };
+        let mut list = vec!(parser.complex()?);
+
+        while parser.eat(',') {
+            list.push(parser.complex()?);
+        }
+
+        parser.skip_whitespace();
+        if parser.position < parser.input.len() { return Err(parser.error()); }
+
+        Ok(SelectorList(list))
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> SelectorError { SelectorError { position: self.position } }
+
+    fn peek(&self) -> Option<char> { self.input[self.position..].chars().next() }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.position;
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) { self.position += c.len_utf8(); }
+        self.position > start
+    }
+
+    //  Consumes `c`, and any surrounding whitespace.
+    fn eat(&mut self, c: char) -> bool {
+        let start = self.position;
+        self.skip_whitespace();
+
+        if self.peek() == Some(c) {
+            self.position += 1;
+            self.skip_whitespace();
+            true
+        } else {
+            self.position = start;
+            false
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, SelectorError> {
+        let rest = &self.input[self.position..];
+        let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_')).unwrap_or(rest.len());
+
+        if end == 0 { return Err(self.error()); }
+
+        self.position += end;
+        Ok(rest[..end].to_string())
+    }
+
+    fn complex(&mut self) -> Result<Complex, SelectorError> {
+        self.skip_whitespace();
+
+        let mut compounds = vec!(self.compound()?);
+        let mut combinators = Vec::new();
+
+        loop {
+            let combinator = if self.eat('>') {
+                Combinator::Child
+            } else if self.skip_whitespace() && !matches!(self.peek(), None | Some(',')) {
+                Combinator::Descendant
+            } else {
+                break;
+            };
+
+            combinators.push(combinator);
+            compounds.push(self.compound()?);
+        }
+
+        let subject = compounds.pop().expect("at least one compound");
+        let ancestors = combinators.into_iter().rev().zip(compounds.into_iter().rev()).collect();
+
+        Ok(Complex { subject, ancestors })
+    }
+
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let mut compound = Compound::default();
+        let start = self.position;
+
+        if self.peek() == Some('*') {
+            self.position += 1;
+        } else if self.peek().is_some_and(char::is_alphabetic) {
+            compound.tag = Some(self.identifier()?.to_ascii_lowercase());
+        }
+
+        loop {
+            match self.peek() {
+            Some('#') => { self.position += 1; compound.ids.push(self.identifier()?); },
+            Some('.') => { self.position += 1; compound.classes.push(self.identifier()?); },
+            Some('[') => {
+                self.position += 1;
+                self.skip_whitespace();
+                let key = self.identifier()?.to_ascii_lowercase();
+                let value = if self.eat('=') { Some(self.value()?) } else { None };
+                if !self.eat(']') { return Err(self.error()); }
+                compound.attributes.push((key, value));
+            },
+            Some(':') => {
+                self.position += 1;
+                if self.identifier()? != "first-child" { return Err(self.error()); }
+                compound.first_child = true;
+            },
+            _ => break,
+            }
+        }
+
+        if self.position == start { return Err(self.error()); }
+
+        Ok(compound)
+    }
+
+    fn value(&mut self) -> Result<String, SelectorError> {
+        match self.peek() {
+        Some(quote @ ('"' | '\'')) => {
+            let rest = &self.input[self.position + 1..];
+            let end = rest.find(quote).ok_or(SelectorError { position: self.input.len() })?;
+            self.position += end + 2;
+            Ok(rest[..end].to_string())
+        },
+        _ => self.identifier(),
+        }
+    }
+}
+
+//
+//  Matching
+//
+impl SelectorList {
+    pub fn matches(&self, element: &ClassElement) -> bool {
+        self.0.iter().any(|complex| complex.matches(element))
+    }
+}
+
+impl Complex {
+    fn matches(&self, element: &ClassElement) -> bool {
+        self.subject.matches(element) && matches_ancestors(&self.ancestors, element)
+    }
+}
+
+//  Whether the ancestors of `element` satisfy `ancestors`, backtracking on descendant
+//  combinators.
+fn matches_ancestors(ancestors: &[(Combinator, Compound)], element: &ClassElement) -> bool {
+    let ((combinator, compound), rest) = match ancestors.split_first() {
+    Some(first) => first,
+    None => return true,
+    };
+
+    let mut parents = element.ancestors().map_while(as_element);
+
+    match combinator {
+    Combinator::Child => {
+        parents.next().is_some_and(|parent| compound.matches(&parent) && matches_ancestors(rest, &parent))
+    },
+    Combinator::Descendant => {
+        parents.any(|parent| compound.matches(&parent) && matches_ancestors(rest, &parent))
+    },
+    }
+}
+
+impl Compound {
+    fn matches(&self, element: &ClassElement) -> bool {
+        let data = element.as_struct();
+
+        if let Some(ref tag) = self.tag {
+            if element.as_trait().tag_name() != tag { return false; }
+        }
+
+        let id = data.get_attribute("id");
+        if !self.ids.iter().all(|i| id.as_deref() == Some(i.as_str())) { return false; }
+
+        let class = data.get_attribute("class").unwrap_or_default();
+        if !self.classes.iter().all(|c| class.split_whitespace().any(|k| k == c)) { return false; }
+
+        let attributes_match = self.attributes.iter().all(|(key, value)| {
+            match (data.get_attribute(key), value) {
+            (Some(actual), Some(expected)) => actual == *expected,
+            (actual, None) => actual.is_some(),
+            (None, Some(_)) => false,
+            }
+        });
+        if !attributes_match { return false; }
+
+        !self.first_child || previous_element_sibling(element).is_none()
+    }
+}
+
+fn previous_element_sibling(element: &ClassElement) -> Option<ClassElement> {
+    let mut previous = element.previous_sibling();
+
+    while let Some(node) = previous {
+        previous = node.previous_sibling();
+        if let Some(element) = as_element(node) { return Some(element); }
+    }
+
+    None
+}
+
+fn as_element(node: ClassNode) -> Option<ClassElement> { node.down_cast().ok() }
+
+//
+//  Queries
+//
+//  Implemented for any handle on a node, whichever its class; only the descendants of
+//  the node are searched, in tree order.
+pub trait Query: Tree {
+    fn query_selector(&self, selectors: &str) -> Result<Option<ClassElement>, SelectorError> {
+        let list = SelectorList::parse(selectors)?;
+        Ok(self.descendants_of::<ClassElement>().find(|e| list.matches(e)))
+    }
+
+    fn query_selector_all(&self, selectors: &str) -> Result<Vec<ClassElement>, SelectorError> {
+        let list = SelectorList::parse(selectors)?;
+        Ok(self.descendants_of::<ClassElement>().filter(|e| list.matches(e)).collect())
+    }
+}
+
+impl<N: Tree + ?Sized> Query for N {}
+
+//  Implemented for any handle on an element, whichever its class.
+pub trait Matches: AsElement {
+    fn matches(&self, selectors: &str) -> Result<bool, SelectorError> {
+        Ok(SelectorList::parse(selectors)?.matches(&self.as_element()))
+    }
+}
+
+impl<E: AsElement + ?Sized> Matches for E {}
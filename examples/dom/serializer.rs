@@ -0,0 +1,101 @@
+//
+//  Serializer: from a tree to markup
+//
+//  The output parses back into the same tree: attributes are emitted in order of their
+//  names, text is escaped, except within raw text elements, and void elements have no
+//  end tag.
+//
+use poly::DownCast;
+
+use crate::element::ClassElement;
+use crate::node::{ClassNode, ClassText, Tree};
+use crate::parser::is_void;
+
+//  Implemented for any handle on a node, whichever its class.
+pub trait Html: Tree {
+    //  The markup of this node, including itself.
+    fn outer_html(&self) -> String {
+        let mut out = String::new();
+        write_node(&mut out, &self.as_node(), false);
+        out
+    }
+
+    //  The markup of the children of this node.
+    fn inner_html(&self) -> String {
+        let node = self.as_node();
+        let raw = as_element(&node).is_some_and(|e| is_raw_text(e.as_trait().tag_name()));
+
+        let mut out = String::new();
+        for child in node.children() {
+            write_node(&mut out, &child, raw);
+        }
+        out
+    }
+}
+
+impl<N: Tree + ?Sized> Html for N {}
+
+//  Elements whose text is not escaped, as the parser does not decode it.
+fn is_raw_text(tag: &str) -> bool { matches!(tag, "script" | "style") }
+
+fn as_element(node: &ClassNode) -> Option<ClassElement> { node.clone().down_cast().ok() }
+
+fn write_node(out: &mut String, node: &ClassNode, raw: bool) {
+    let text: Option<ClassText> = node.clone().down_cast().ok();
+
+    if let Some(text) = text {
+        if raw {
+            out.push_str(&text.as_struct().text());
+        } else {
+            escape(out, &text.as_struct().text(), false);
+        }
+        return;
+    }
+
+    let element = match as_element(node) {
+    Some(element) => element,
+    //  A document, or an unknown node, only contributes its children.
+    None => {
+        for child in node.children() { write_node(out, &child, false); }
+        return;
+    },
+    };
+
+    let tag = element.as_trait().tag_name();
+
+    out.push('<');
+    out.push_str(tag);
+
+    for (key, value) in element.as_struct().attributes() {
+        out.push(' ');
+        out.push_str(&key);
+        out.push_str("=\"");
+        escape(out, &value, true);
+        out.push('"');
+    }
+
+    out.push('>');
+
+    if is_void(tag) { return; }
+
+    for child in node.children() {
+        write_node(out, &child, is_raw_text(tag));
+    }
+
+    out.push_str("</");
+    out.push_str(tag);
+    out.push('>');
+}
+
+fn escape(out: &mut String, text: &str, attribute: bool) {
+    for c in text.chars() {
+        match c {
+        '&' => out.push_str("&amp;"),
+        '\u{a0}' => out.push_str("&nbsp;"),
+        '"' if attribute => out.push_str("&quot;"),
+        '<' if !attribute => out.push_str("&lt;"),
+        '>' if !attribute => out.push_str("&gt;"),
+        c => out.push(c),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//
+//  A tentative implementation of RFC: Disjoint Polymorphism
+//
+//  [Compiler] part
+//
+//  Until the compiler implements the intrinsics for struct and trait extension, these
+//  procedural macros generate them:
+//
+//  - `#[derive(PolyStruct)]`, see poly_struct,
+//  - `#[poly_trait]`, see poly_trait.
+//
+extern crate proc_macro;
+
+mod poly_struct;
+mod poly_trait;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemTrait};
+
+#[proc_macro_derive(PolyStruct, attributes(parent))]
+pub fn derive_poly_struct(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match poly_struct::expand(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(error) => error.to_compile_error().into(),
+    }
+}
+
+//  Implementation detail of `#[derive(PolyStruct)]`, see poly_struct.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __poly_extend_struct(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as poly_struct::Ancestors);
+
+    match poly_struct::expand_ancestors(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(error) => error.to_compile_error().into(),
+    }
+}
+
+//  Implementation detail of `#[poly_trait]`, see poly_trait.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __poly_extend_trait(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as poly_trait::Ancestors);
+
+    match poly_trait::expand_ancestors(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(error) => error.to_compile_error().into(),
+    }
+}
+
+//  Implementation detail of `poly_hierarchy!`, see poly_trait.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __poly_struct_traits(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as poly_trait::StructTraits);
+
+    match poly_trait::expand_struct_traits(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(error) => error.to_compile_error().into(),
+    }
+}
+
+//  Implementation detail of `poly_hierarchy!`, see poly_trait.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __poly_declare_trait(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as poly_trait::Declared);
+
+    poly_trait::expand_declared(&input).into()
+}
+
+#[proc_macro_attribute]
+pub fn poly_trait(args: TokenStream, input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as ItemTrait);
+
+    match poly_trait::expand(args.into(), &item) {
+    Ok(tokens) => tokens.into(),
+    Err(error) => error.to_compile_error().into(),
+    }
+}
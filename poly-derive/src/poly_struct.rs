@@ -0,0 +1,336 @@
+//
+//  A tentative implementation of RFC: Disjoint Polymorphism
+//
+//  [Compiler] part: #[derive(PolyStruct)]
+//
+//  Stands in for the struct extension syntax, following gereeter's `#[parent]` attribute:
+//
+//      #[repr(C)]
+//      #[derive(PolyStruct)]
+//      struct ElementData {
+//          #[parent]
+//          node: NodeData,
+//          attrs: HashMap<String, String>,
+//      }
+//
+//  - ExtendStruct<Self> and ExtendStruct<()> are implemented, as well as ExtendStruct<P>
+//    for each parent P and, transitively, each of their parents.
+//  - UniqueExtendStruct<P> is only implemented when a single P sub-object exists.
+//  - FirstExtendStruct<P> is only implemented when P is known to sit at offset 0, that is
+//    when it is the first field of a #[repr(C)] struct, transitively.
+//  - PolyStruct is implemented, collecting the offsets of all sub-objects for StructInfo.
+//
+//  `#[repr(packed)]` is rejected, as a parent could then be misaligned.
+//
+//  Transitivity is achieved by emitting, alongside each struct X, a hidden macro
+//  `__poly_extend_X!` listing X (and X's parents) as ancestors of a descendant; as a
+//  result parents must be declared in the same crate, and they (as well as their own
+//  parents) must be in scope wherever a descendant is derived.
+//
+//  Parent ambiguity, that is the same ancestor reached through several parents, is
+//  supported: the ancestors are first collected, walking the `__poly_extend_X!` macros
+//  one after the other through `poly::__poly_next!`, then `poly::__poly_extend_struct!`
+//  implements ExtendStruct<P> once per ancestor P, with all its offsets.
+//
+//  Ancestors are identified by name, as their `__poly_extend_X!` macros are: an ancestor
+//  spelled differently along different paths, say `a::Node` and `Node`, is implemented once,
+//  and the spellings are checked at compile-time to name the same struct.
+//
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+
+use syn::parse::{Parse, ParseStream};
+use syn::{bracketed, parenthesized, Token};
+use syn::{Attribute, Data, DeriveInput, Error, Expr, Ident, LitBool, Member, Meta, Path, Type};
+
+struct Parent {
+    member: Member,
+    path: Path,
+    first: bool,
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "PolyStruct does not support generic structs"));
+    }
+
+    let fields = match input.data {
+    Data::Struct(ref data) => &data.fields,
+    _ => return Err(Error::new_spanned(&input.ident, "PolyStruct can only be derived for structs")),
+    };
+
+    let is_repr_c = is_repr_c(&input.attrs)?;
+
+    let mut parents = Vec::new();
+
+    for (index, field) in fields.iter().enumerate() {
+        let Some(attr) = field.attrs.iter().find(|a| a.path().is_ident("parent")) else { continue; };
+
+        if !matches!(attr.meta, Meta::Path(_)) {
+            return Err(Error::new_spanned(attr, "#[parent] takes no argument"));
+        }
+
+        let member = match field.ident {
+        Some(ref ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(index.into()),
+        };
+
+        let path = match field.ty {
+        Type::Path(ref p) if p.qself.is_none() => p.path.clone(),
+        ref other => return Err(Error::new_spanned(other, "a #[parent] must be a plain struct")),
+        };
+
+        parents.push(Parent { member, path, first: is_repr_c && index == 0 });
+    }
+
+    let name = &input.ident;
+    let extend_macro = format_ident!("__poly_extend_{}", name);
+
+    let members: Vec<_> = parents.iter().map(|p| &p.member).collect();
+    let paths: Vec<_> = parents.iter().map(|p| &p.path).collect();
+    let firsts: Vec<_> = parents.iter().map(|p| p.first).collect();
+    let macros = parents.iter().map(|p| extend_macro_of(&p.path)).collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        unsafe impl ::poly::ExtendStruct<#name> for #name {
+            fn offsets() -> &'static [isize] { &[0] }
+        }
+        unsafe impl ::poly::UniqueExtendStruct<#name> for #name {}
+        unsafe impl ::poly::FirstExtendStruct<#name> for #name {}
+
+        unsafe impl ::poly::ExtendStruct<()> for #name {
+            fn offsets() -> &'static [isize] { &[0] }
+        }
+        unsafe impl ::poly::UniqueExtendStruct<()> for #name {}
+        unsafe impl ::poly::FirstExtendStruct<()> for #name {}
+
+        ::poly::__poly_next! {
+            #name;
+            [#( (#macros, [#paths], [::core::mem::offset_of!(#name, #members) as isize], #firsts) )*];
+            []
+        }
+
+        unsafe impl ::poly::PolyStruct for #name {
+            fn sub_objects(collector: &mut Vec<(::poly::StructId, isize)>, base: isize) {
+                collector.push((::poly::struct_id::<#name>(), base));
+                #(
+                    <#paths as ::poly::PolyStruct>::sub_objects(
+                        collector,
+                        base + ::core::mem::offset_of!(#name, #members) as isize,
+                    );
+                )*
+            }
+
+            fn offsets(id: ::poly::StructId) -> &'static [isize] {
+                static CACHE: ::poly::__private::OffsetsCache = ::poly::__private::OffsetsCache::new();
+                CACHE.offsets::<#name>(id)
+            }
+        }
+
+        //  Lists #name, spelled $P by its child, at offset $off within the descendant $C, as
+        //  well as its parents, then moves on to the next ancestor in $todo; `true` if the
+        //  offset is known to be 0.
+        //
+        //  The parents are spelled as within this module, yet resolved within the module of
+        //  $C: the type of each parent field is checked to be the one so resolved.
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #extend_macro {
+            (@collect $C:ty; [$P:ty]; [$off:expr]; true; [$($todo:tt)*]; [$($acc:tt)*]) => {
+                #( const _: fn(&$P) -> &#paths = |child| &child.#members; )*
+
+                ::poly::__poly_next! {
+                    $C;
+                    [
+                        #( (#macros, [#paths], [$off + ::core::mem::offset_of!($P, #members) as isize], #firsts) )*
+                        $($todo)*
+                    ];
+                    [$($acc)* ($P, [$off], true)]
+                }
+            };
+            (@collect $C:ty; [$P:ty]; [$off:expr]; false; [$($todo:tt)*]; [$($acc:tt)*]) => {
+                #( const _: fn(&$P) -> &#paths = |child| &child.#members; )*
+
+                ::poly::__poly_next! {
+                    $C;
+                    [
+                        #( (#macros, [#paths], [$off + ::core::mem::offset_of!($P, #members) as isize], false) )*
+                        $($todo)*
+                    ];
+                    [$($acc)* ($P, [$off], false)]
+                }
+            };
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub(crate) use #extend_macro;
+    })
+}
+
+//  The ancestors of a struct, as collected by the `__poly_extend_X!` macros:
+//
+//      Struct; (Ancestor, [offset], first)*
+//
+//  The same ancestor may appear several times, at different offsets, and spelled differently.
+pub struct Ancestors {
+    target: Type,
+    ancestors: Vec<(Type, Expr, bool)>,
+}
+
+impl Parse for Ancestors {
+    fn parse(input: ParseStream) -> syn::Result<Ancestors> {
+        let target = input.parse()?;
+        input.parse::<Token![;]>()?;
+
+        let mut ancestors = Vec::new();
+
+        while !input.is_empty() {
+            let content;
+            parenthesized!(content in input);
+
+            let ancestor = content.parse()?;
+            content.parse::<Token![,]>()?;
+
+            let offset;
+            bracketed!(offset in content);
+            let offset = offset.parse()?;
+            content.parse::<Token![,]>()?;
+
+            let first: LitBool = content.parse()?;
+
+            ancestors.push((ancestor, offset, first.value));
+        }
+
+        Ok(Ancestors { target, ancestors })
+    }
+}
+
+//  All the appearances of an ancestor, by name.
+struct AncestorGroup<'a> {
+    ancestor: &'a Type,
+    offsets: Vec<&'a Expr>,
+    first: bool,
+    //  Other spellings of `ancestor`.
+    spellings: Vec<&'a Type>,
+}
+
+//  Implements ExtendStruct<P>, and if applicable UniqueExtendStruct<P> and FirstExtendStruct<P>,
+//  once for each ancestor P.
+pub fn expand_ancestors(input: &Ancestors) -> syn::Result<TokenStream> {
+    let target = &input.target;
+
+    //  Grouped by name, in order of first appearance.
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, AncestorGroup> = HashMap::new();
+
+    for (ancestor, offset, first) in &input.ancestors {
+        let ancestor = ungroup(ancestor);
+        let key = ancestor_name(ancestor)?.to_string();
+
+        let group = groups.entry(key.clone()).or_insert_with(|| {
+            order.push(key);
+            AncestorGroup { ancestor, offsets: Vec::new(), first: true, spellings: Vec::new() }
+        });
+
+        group.offsets.push(offset);
+        group.first = group.first && *first;
+
+        let spelling = quote!(#ancestor).to_string();
+        let known = Some(group.ancestor).into_iter()
+            .chain(group.spellings.iter().copied())
+            .any(|t| quote!(#t).to_string() == spelling);
+
+        if !known { group.spellings.push(ancestor); }
+    }
+
+    let impls = order.iter().map(|key| {
+        let AncestorGroup { ancestor, ref offsets, first, ref spellings } = groups[key];
+        let count = offsets.len();
+
+        let unique = (count == 1).then(|| quote! {
+            unsafe impl ::poly::UniqueExtendStruct<#ancestor> for #target {}
+        });
+
+        let first = (count == 1 && first).then(|| quote! {
+            unsafe impl ::poly::FirstExtendStruct<#ancestor> for #target {}
+        });
+
+        quote! {
+            unsafe impl ::poly::ExtendStruct<#ancestor> for #target {
+                fn offsets() -> &'static [isize] {
+                    static OFFSETS: [isize; #count] = ::poly::__private::sort_offsets([#(#offsets),*]);
+                    &OFFSETS
+                }
+            }
+            #unique
+            #first
+
+            //  Fails to compile if two spellings of the name designate different structs.
+            #(
+                const _: fn(&#ancestor) -> &#spellings = |ancestor| ancestor;
+            )*
+        }
+    });
+
+    Ok(quote! { #(#impls)* })
+}
+
+//  The ancestors are forwarded as `ty` fragments, hence are wrapped in invisible groups.
+fn ungroup(ancestor: &Type) -> &Type {
+    match *ancestor {
+    Type::Group(ref group) => ungroup(&group.elem),
+    ref other => other,
+    }
+}
+
+//  The name of the ancestor, that of its `__poly_extend_X!` macro.
+fn ancestor_name(ancestor: &Type) -> syn::Result<&Ident> {
+    match *ancestor {
+    Type::Path(ref p) if p.qself.is_none() => Ok(&p.path.segments.last().expect("Non-empty path").ident),
+    ref other => Err(Error::new_spanned(other, "an ancestor must be a plain struct")),
+    }
+}
+
+//  Path to the `__poly_extend_X!` macro of the parent at `path`.
+pub fn extend_macro_of(path: &Path) -> syn::Result<Path> {
+    let mut result = path.clone();
+
+    let last = result.segments.last_mut().expect("Non-empty path");
+
+    if !last.arguments.is_empty() {
+        return Err(Error::new_spanned(path, "a #[parent] cannot be generic"));
+    }
+
+    last.ident = format_ident!("__poly_extend_{}", last.ident);
+
+    Ok(result)
+}
+
+fn is_repr_c(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut result = false;
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                result = true;
+            }
+
+            if meta.path.is_ident("packed") {
+                return Err(meta.error("PolyStruct does not support #[repr(packed)], a parent could be misaligned"));
+            }
+
+            //  Skip over the arguments of align(N) and the like.
+            if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream>()?;
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(result)
+}
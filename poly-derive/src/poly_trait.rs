@@ -0,0 +1,385 @@
+//
+//  A tentative implementation of RFC: Disjoint Polymorphism
+//
+//  [Compiler] part: #[poly_trait]
+//
+//  Stands in for the compiler's handling of trait extension:
+//
+//      #[poly_trait]
+//      trait Element: Node {
+//          fn do_the_thing(&self);
+//      }
+//
+//  - ExtendTrait, FirstExtendTrait and TraitExtendTrait are implemented for dyn Element,
+//    towards itself, each of its super-traits and, transitively, each of theirs.
+//  - PolyTrait is implemented, laying out the v-table block of Element: Element first,
+//    then the block of each super-trait in declaration order, so that the block of any
+//    ancestor is a contiguous part of it; TraitExtendTrait::offset() is the slot of the
+//    first v-table of the ancestor in this block.
+//  - `<dyn Element>::__poly_register_v_tables::<S>` registers the v-table block of S.
+//  - The TraitInfo of Element is registered at link-time; `poly_hierarchy!` only lists the
+//    structs implementing it.
+//  - PolyObject is added to the super-traits, so that `&dyn Element` may be cast.
+//
+//  Only super-traits which are themselves #[poly_trait] take part in the hierarchy; common
+//  super-traits such as RawClone, Send, Sync or Debug are skipped automatically, others
+//  can be skipped with #[poly_trait(skip(Foo, Bar))].
+//
+//  As for #[derive(PolyStruct)], transitivity relies on a hidden macro `__poly_trait_X!`,
+//  emitted alongside each trait X; as a result super-traits must be declared in the same
+//  crate, and be in scope wherever a derived trait is declared.
+//
+//  The ancestors are first collected, walking the `__poly_trait_X!` macros one after the
+//  other through `poly::__poly_trait_next!`, then `poly::__poly_extend_trait!` implements
+//  the extension traits once per ancestor, even when reached through several super-traits.
+//
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Token};
+use syn::{parse_quote, Error, ItemTrait, Path, Type, TypeParamBound};
+
+//  Super-traits which never take part in the hierarchy, spelled either in full, with or
+//  without the leading `std`, `core` or `alloc`, or by their sole name.
+const SKIPPED: &[&str] = &[
+    "any::Any", "fmt::Debug", "fmt::Display", "marker::Send", "marker::Sized", "marker::Sync",
+    "marker::Unpin", "poly::PolyObject", "poly::RawClone",
+];
+
+pub fn expand(args: TokenStream, item: &ItemTrait) -> syn::Result<TokenStream> {
+    let mut skipped: Vec<String> = Vec::new();
+
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("skip") {
+            meta.parse_nested_meta(|skip| {
+                skipped.push(path_string(&skip.path));
+                Ok(())
+            })
+        } else {
+            Err(meta.error("unsupported #[poly_trait] argument, expected skip(...)"))
+        }
+    });
+    syn::parse::Parser::parse2(parser, args)?;
+
+    if !item.generics.params.is_empty() {
+        return Err(Error::new_spanned(&item.generics, "#[poly_trait] does not support generic traits"));
+    }
+
+    let mut supers: Vec<&Path> = Vec::new();
+    let mut has_poly_object = false;
+
+    for bound in &item.supertraits {
+        let TypeParamBound::Trait(ref bound) = *bound else { continue; };
+
+        let path = path_string(&bound.path);
+
+        has_poly_object |= is_known(&path, "poly::PolyObject");
+
+        if SKIPPED.iter().any(|s| is_known(&path, s)) || skipped.contains(&path) { continue; }
+
+        supers.push(&bound.path);
+    }
+
+    //  The struct behind a `&dyn X` is found through PolyObject, see internal.
+    let mut decl = item.clone();
+
+    if !has_poly_object {
+        decl.supertraits.push(parse_quote!(::poly::PolyObject));
+    }
+
+    let name = &item.ident;
+    let vis = &item.vis;
+    let trait_macro = format_ident!("__poly_trait_{}", name);
+    let macros = supers.iter().map(|p| trait_macro_of(p)).collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #decl
+
+        unsafe impl ::poly::ExtendTrait<dyn #name> for dyn #name {}
+        unsafe impl ::poly::FirstExtendTrait<dyn #name> for dyn #name {}
+        unsafe impl ::poly::TraitExtendTrait<dyn #name> for dyn #name {
+            fn offset() -> isize { 0 }
+        }
+
+        ::poly::__poly_trait_next! {
+            dyn #name;
+            [#( (#macros, [#supers]) )*];
+            []
+        }
+
+        unsafe impl ::poly::PolyTrait for dyn #name {
+            fn collect_traits(collector: &mut Vec<::poly::TraitId>) {
+                collector.push(::poly::trait_id::<dyn #name>());
+                #(
+                    <dyn #supers as ::poly::PolyTrait>::collect_traits(collector);
+                )*
+            }
+
+            fn block() -> &'static [::poly::TraitId] {
+                static BLOCK: ::std::sync::OnceLock<Vec<::poly::TraitId>> =
+                    ::std::sync::OnceLock::new();
+
+                BLOCK.get_or_init(|| {
+                    let mut block = Vec::new();
+                    <Self as ::poly::PolyTrait>::collect_traits(&mut block);
+                    block
+                })
+            }
+        }
+
+        ::poly::__private::inventory::submit! {
+            ::poly::__private::trait_registration::<dyn #name>()
+        }
+
+        impl dyn #name {
+            //  Registers the v-table block of S, laid out as per PolyTrait::block().
+            #[doc(hidden)]
+            #vis fn __poly_register_v_tables<S>(
+                tables: &mut ::poly::__private::VTableRegistryTables,
+                indices: &mut ::poly::__private::VTableRegistryIndices,
+            )
+                where S: #name + 'static
+            {
+                use ::poly::{VTable, struct_id, trait_id};
+
+                let head = (trait_id::<dyn #name>(), struct_id::<S>());
+
+                let mut block = vec!(::poly::make_vtable!(dyn #name, S));
+                #(
+                    #macros! { @v_tables S block }
+                )*
+
+                //  An ancestor reached through several super-traits has several v-tables in
+                //  the block; the first one stands for all.
+                for (index, v_table) in block.iter().enumerate().skip(1) {
+                    let id = v_table.trait_info().trait_id();
+                    if block[..index].iter().any(|v| v.trait_info().trait_id() == id) { continue; }
+
+                    let offset = index * ::std::mem::size_of::<VTable>();
+                    indices.push(((id, head.1), head, offset as isize));
+                }
+
+                tables.push((head, block.into_boxed_slice()));
+            }
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #trait_macro {
+            //  Lists #name, spelled $T by its sub-trait, as well as its super-traits, as
+            //  ancestors of $X, then moves on to the next ancestor in $todo.
+            (@collect $X:ty; [$T:path]; [$($todo:tt)*]; [$($acc:tt)*]) => {
+                ::poly::__poly_trait_next! {
+                    $X;
+                    [#( (#macros, [#supers]) )* $($todo)*];
+                    [$($acc)* ($T)]
+                }
+            };
+            //  Pushes the v-tables of the block of #name for $S into $block.
+            (@v_tables $S:ident $block:ident) => {
+                $block.push(::poly::make_vtable!(dyn #name, $S));
+                #(
+                    #macros! { @v_tables $S $block }
+                )*
+            };
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub(crate) use #trait_macro;
+    })
+}
+
+//  The ancestors of a trait, or of the trait implemented by a struct, as collected by the
+//  `__poly_trait_X!` macros:
+//
+//      dyn Trait; (Ancestor)*
+//      Struct; (Trait) (Ancestor)*
+//
+//  The same ancestor appears several times when reached through several super-traits.
+pub struct Ancestors {
+    target: Type,
+    ancestors: Vec<Path>,
+}
+
+impl Parse for Ancestors {
+    fn parse(input: ParseStream) -> syn::Result<Ancestors> {
+        let target = input.parse()?;
+        input.parse::<Token![;]>()?;
+
+        let mut ancestors = Vec::new();
+
+        while !input.is_empty() {
+            let content;
+            parenthesized!(content in input);
+
+            ancestors.push(content.parse()?);
+        }
+
+        Ok(Ancestors { target, ancestors })
+    }
+}
+
+//  Implements ExtendTrait<dyn A>, FirstExtendTrait<dyn A> and TraitExtendTrait<dyn A> once
+//  for each ancestor A of a trait; only ExtendTrait<dyn A> for a struct.
+//
+//  Ancestors are identified by name, as their `__poly_trait_X!` macros are; an ancestor
+//  spelled differently along different paths is rejected, as it cannot be told apart from
+//  two distinct traits sharing a name.
+pub fn expand_ancestors(input: &Ancestors) -> syn::Result<TokenStream> {
+    let target = &input.target;
+
+    let mut ancestors: Vec<&Path> = Vec::new();
+    let mut spellings: HashMap<String, String> = HashMap::new();
+
+    for ancestor in &input.ancestors {
+        let name = ancestor.segments.last().expect("Non-empty path").ident.to_string();
+        let spelling = path_string(ancestor);
+
+        match spellings.get(&name) {
+        Some(known) if *known == spelling => continue,
+        Some(known) => return Err(Error::new_spanned(
+            ancestor,
+            format!("super-trait `{}` is reached both as `{}` and as `{}`, spell it the same way", name, known, spelling),
+        )),
+        None => (),
+        }
+
+        spellings.insert(name, spelling);
+        ancestors.push(ancestor);
+    }
+
+    //  Passed along as a `$X:ty` fragment, the target comes wrapped in an invisible group.
+    let mut inner = target;
+    while let Type::Group(group) = inner { inner = &group.elem; }
+
+    if !matches!(inner, Type::TraitObject(_)) {
+        return Ok(quote! {
+            #(
+                unsafe impl ::poly::ExtendTrait<dyn #ancestors> for #target {}
+            )*
+        });
+    }
+
+    Ok(quote! {
+        #(
+            unsafe impl ::poly::ExtendTrait<dyn #ancestors> for #target {}
+            unsafe impl ::poly::FirstExtendTrait<dyn #ancestors> for #target {}
+            unsafe impl ::poly::TraitExtendTrait<dyn #ancestors> for #target {
+                fn offset() -> isize { ::poly::__private::trait_slot::<#target, dyn #ancestors>() }
+            }
+        )*
+    })
+}
+
+//  A struct implementing a trait, as listed by `poly_hierarchy!`:
+//
+//      Struct impl Trait
+pub struct StructTraits {
+    target: Type,
+    head: Path,
+}
+
+impl Parse for StructTraits {
+    fn parse(input: ParseStream) -> syn::Result<StructTraits> {
+        let target = input.parse()?;
+        input.parse::<Token![impl]>()?;
+        let head = input.parse()?;
+
+        Ok(StructTraits { target, head })
+    }
+}
+
+//  Implements ExtendTrait<dyn A> for the struct, towards its trait and each of the trait's
+//  ancestors, collected through the `__poly_trait_X!` macros as for a trait.
+pub fn expand_struct_traits(input: &StructTraits) -> syn::Result<TokenStream> {
+    let (target, head) = (&input.target, &input.head);
+    let trait_macro = trait_macro_of(head)?;
+
+    Ok(quote! {
+        ::poly::__poly_trait_next! {
+            #target;
+            [(#trait_macro, [#head])];
+            []
+        }
+    })
+}
+
+//  A trait declared by `poly_hierarchy!`, along with all its ancestors:
+//
+//      Trait: Ancestor: ...
+pub struct Declared {
+    name: syn::Ident,
+    ancestors: Vec<Path>,
+}
+
+impl Parse for Declared {
+    fn parse(input: ParseStream) -> syn::Result<Declared> {
+        let name = input.parse()?;
+
+        let mut ancestors = Vec::new();
+
+        while !input.is_empty() {
+            input.parse::<Token![:]>()?;
+            ancestors.push(input.parse()?);
+        }
+
+        Ok(Declared { name, ancestors })
+    }
+}
+
+//  Emits the `__poly_trait_X!` macro of a trait declared by `poly_hierarchy!`, so that the
+//  structs implementing it reach its ancestors as they would those of a #[poly_trait].
+//
+//  The declaration spells out every ancestor already, hence no walk of their own macros.
+pub fn expand_declared(input: &Declared) -> TokenStream {
+    let ancestors = &input.ancestors;
+    let trait_macro = format_ident!("__poly_trait_{}", input.name);
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #trait_macro {
+            (@collect $X:ty; [$T:path]; [$($todo:tt)*]; [$($acc:tt)*]) => {
+                ::poly::__poly_trait_next! {
+                    $X;
+                    [$($todo)*];
+                    [$($acc)* ($T) #( (#ancestors) )*]
+                }
+            };
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub(crate) use #trait_macro;
+    }
+}
+
+//  `a::b::C`, whether spelled with a leading `::` or not.
+fn path_string(path: &Path) -> String {
+    path.segments.iter().map(|s| s.ident.to_string()).collect::<Vec<_>>().join("::")
+}
+
+//  Whether `path` designates the well-known `known`, see SKIPPED.
+fn is_known(path: &str, known: &str) -> bool {
+    let name = known.rsplit("::").next().expect("Non-empty path");
+
+    path == name || path == known || ["std", "core", "alloc"].iter().any(|c| path == format!("{}::{}", c, known))
+}
+
+//  Path to the `__poly_trait_X!` macro of the super-trait at `path`.
+fn trait_macro_of(path: &Path) -> syn::Result<Path> {
+    let mut result = path.clone();
+
+    let last = result.segments.last_mut().expect("Non-empty path");
+
+    if !last.arguments.is_empty() {
+        return Err(Error::new_spanned(path, "a #[poly_trait] super-trait cannot be generic"));
+    }
+
+    last.ident = format_ident!("__poly_trait_{}", last.ident);
+
+    Ok(result)
+}
@@ -0,0 +1,229 @@
+//
+//  A tentative implementation of RFC: Disjoint Polymorphism
+//
+//  [Library] part: declaring a hierarchy
+//
+//  Until the compiler implements the marker traits and emits the type-infos and v-tables,
+//  `poly_hierarchy!` generates them from a declaration of the hierarchy:
+//
+//      poly_hierarchy! {
+//          trait Node;
+//          trait Element: Node;
+//
+//          struct NodeData impl Node;
+//          struct ElementData: NodeData impl Element;
+//          #[derive(PolyStruct)] struct HTMLImageElement impl Element;
+//      }
+//
+//  - `trait X: A: B` declares that X extends A which extends B; the v-table block of
+//    any struct implementing X is laid out as [X, A, B].
+//    Plain `&dyn X` may only be cast if X extends `PolyObject`, which must be spelled out.
+//  - Traits defined with `#[poly_trait]` are not listed, the attribute registering them;
+//    listing one is an error, as it would be registered twice.
+//  - `struct S: P: Q impl X` declares that S extends P which extends Q, and implements X
+//    and therefore its ancestors, whether X is listed or defined with `#[poly_trait]`.
+//  - `#[derive(PolyStruct)] struct S impl X` declares that S implements X, its parents
+//    being handled by the derive itself.
+//
+//  With the `struct S: P: Q` form, each struct extends its parent through its `_first_parent`
+//  field, whose offset is checked at compile-time to be 0, as required by `FirstExtendStruct`.
+//
+//  The macro also generates the `register_struct_info`, `register_trait_info` and
+//  `register_vtables` functions of the module, and submits them as a `Registration`
+//  collected at link-time; as a result it can only be invoked once per module.
+//
+
+#[macro_export]
+macro_rules! poly_hierarchy(
+    //
+    //  Items
+    //
+    (@munch [$($traits:tt)*] [$($structs:tt)*]
+        trait $T:ident $(: $P:ident)* ; $($rest:tt)*
+    ) => {
+        $crate::poly_hierarchy!(@trait $T $(: $P)*);
+        $crate::poly_hierarchy!(@munch [$($traits)* ($T)] [$($structs)*] $($rest)*);
+    };
+    (@munch [$($traits:tt)*] [$($structs:tt)*]
+        #[poly_trait] trait $T:ident ; $($rest:tt)*
+    ) => {
+        ::std::compile_error!(concat!(
+            "`", stringify!($T), "` is registered by #[poly_trait], remove it from poly_hierarchy!",
+        ));
+    };
+    (@munch [$($traits:tt)*] [$($structs:tt)*]
+        struct $S:ident $(: $P:ident)* impl $T:ident ; $($rest:tt)*
+    ) => {
+        $crate::poly_hierarchy!(@struct $S $(: $P)*);
+        $crate::__poly_struct_traits! { $S impl $T }
+        $crate::poly_hierarchy!(@munch [$($traits)*] [$($structs)* ($S $T)] $($rest)*);
+    };
+    (@munch [$($traits:tt)*] [$($structs:tt)*]
+        #[derive(PolyStruct)] struct $S:ident impl $T:ident ; $($rest:tt)*
+    ) => {
+        $crate::__poly_struct_traits! { $S impl $T }
+        $crate::poly_hierarchy!(@munch [$($traits)*] [$($structs)* ($S $T)] $($rest)*);
+    };
+    (@munch [$(($T:ident))*] [$(($S:ident $HT:ident))*]) => {
+        //  Either list may be empty, #[poly_trait] traits registering themselves.
+        #[allow(clippy::ptr_arg)]
+        pub fn register_struct_info(
+            collector: &mut Vec<($crate::StructId, $crate::StructInfo)>,
+        )
+        {
+            use $crate::PolyStruct;
+
+            $(
+                collector.push($crate::__private::make_struct_info::<$S>(<$S as PolyStruct>::offsets));
+            )*
+        }
+
+        #[allow(clippy::ptr_arg)]
+        pub fn register_trait_info(
+            collector: &mut Vec<($crate::TraitId, $crate::TraitInfo)>,
+        )
+        {
+            $(
+                collector.push($crate::__private::make_trait_info::<dyn $T>());
+            )*
+        }
+
+        pub fn register_vtables(
+            tables: &mut $crate::__private::VTableRegistryTables,
+            indices: &mut $crate::__private::VTableRegistryIndices,
+        )
+        {
+            $(
+                <dyn $HT>::__poly_register_v_tables::<$S>(tables, indices);
+            )*
+        }
+
+        $crate::__private::inventory::submit! {
+            $crate::Registration::new(register_struct_info, register_trait_info, register_vtables)
+        }
+    };
+
+    //
+    //  Traits
+    //
+    (@trait $T:ident $(: $P:ident)*) => {
+        unsafe impl $crate::ExtendTrait<dyn $T> for dyn $T {}
+        unsafe impl $crate::FirstExtendTrait<dyn $T> for dyn $T {}
+        unsafe impl $crate::TraitExtendTrait<dyn $T> for dyn $T { fn offset() -> isize { 0 } }
+
+        $crate::poly_hierarchy!(@trait_parents $T [1] $($P)*);
+
+        //  Lets the structs implementing $T reach its ancestors, see `__poly_trait_next!`.
+        $crate::__poly_declare_trait! { $T $(: $P)* }
+
+        impl dyn $T {
+            //  Registers the v-table block of S, laid out as [T, P...].
+            #[doc(hidden)]
+            pub fn __poly_register_v_tables<S>(
+                tables: &mut $crate::__private::VTableRegistryTables,
+                indices: &mut $crate::__private::VTableRegistryIndices,
+            )
+                where S: $T + $crate::ExtendTrait<dyn $T>
+                         $(+ $crate::ExtendTrait<dyn $P>)* + 'static
+            {
+                use $crate::{VTable, struct_id, trait_id};
+
+                let head = (trait_id::<dyn $T>(), struct_id::<S>());
+
+                tables.push((head, Box::new([
+                    $crate::make_vtable!(dyn $T, S)
+                    $(, $crate::make_vtable!(dyn $P, S))*
+                ])));
+
+                let parents: &[_] = &[$(trait_id::<dyn $P>()),*];
+
+                for (index, parent) in parents.iter().enumerate() {
+                    let offset = (index + 1) * ::std::mem::size_of::<VTable>();
+                    indices.push(((*parent, head.1), head, offset as isize));
+                }
+            }
+        }
+    };
+
+    (@trait_parents $T:ident [$index:expr] $P:ident $($rest:ident)*) => {
+        unsafe impl $crate::ExtendTrait<dyn $P> for dyn $T {}
+        unsafe impl $crate::FirstExtendTrait<dyn $P> for dyn $T {}
+        unsafe impl $crate::TraitExtendTrait<dyn $P> for dyn $T {
+            fn offset() -> isize {
+                ($index * ::std::mem::size_of::<$crate::VTable>()) as isize
+            }
+        }
+
+        $crate::poly_hierarchy!(@trait_parents $T [$index + 1] $($rest)*);
+    };
+    (@trait_parents $T:ident [$index:expr]) => {};
+
+    //
+    //  Structs
+    //
+    (@struct $S:ident $(: $P:ident)*) => {
+        unsafe impl $crate::ExtendStruct<()> for $S {
+            fn offsets() -> &'static [isize] { static ZERO: [isize; 1] = [0]; &ZERO }
+        }
+        unsafe impl $crate::UniqueExtendStruct<()> for $S {}
+        unsafe impl $crate::FirstExtendStruct<()> for $S {}
+
+        unsafe impl $crate::ExtendStruct<$S> for $S {
+            fn offsets() -> &'static [isize] { static ZERO: [isize; 1] = [0]; &ZERO }
+        }
+        unsafe impl $crate::UniqueExtendStruct<$S> for $S {}
+        unsafe impl $crate::FirstExtendStruct<$S> for $S {}
+
+        $crate::poly_hierarchy!(@struct_parents $S [0] $S $(: $P)*);
+
+        unsafe impl $crate::PolyStruct for $S {
+            fn sub_objects(collector: &mut Vec<($crate::StructId, isize)>, base: isize) {
+                collector.push(($crate::struct_id::<$S>(), base));
+                $crate::poly_hierarchy!(@struct_sub_objects $S collector base $($P)*);
+            }
+
+            fn offsets(id: $crate::StructId) -> &'static [isize] {
+                static CACHE: $crate::__private::OffsetsCache = $crate::__private::OffsetsCache::new();
+                CACHE.offsets::<$S>(id)
+            }
+        }
+    };
+
+    //  Only the direct parent is visited, it visits its own parents in turn.
+    (@struct_sub_objects $S:ident $collector:ident $base:ident $P:ident $($rest:ident)*) => {
+        <$P as $crate::PolyStruct>::sub_objects(
+            $collector,
+            $base + ::std::mem::offset_of!($S, _first_parent) as isize,
+        );
+    };
+    (@struct_sub_objects $S:ident $collector:ident $base:ident) => {};
+
+    (@struct_parents $S:ident [$offset:expr] $C:ident : $P:ident $(: $rest:ident)*) => {
+        unsafe impl $crate::ExtendStruct<$P> for $S {
+            fn offsets() -> &'static [isize] {
+                static OFFSETS: [isize; 1] =
+                    [$offset + ::std::mem::offset_of!($C, _first_parent) as isize];
+                &OFFSETS
+            }
+        }
+
+        const _: () = assert!(
+            ::std::mem::offset_of!($C, _first_parent) == 0,
+            concat!(stringify!($C), "::_first_parent should be at offset 0, consider #[repr(C)]."),
+        );
+        unsafe impl $crate::UniqueExtendStruct<$P> for $S {}
+        unsafe impl $crate::FirstExtendStruct<$P> for $S {}
+
+        $crate::poly_hierarchy!(
+            @struct_parents $S [$offset + ::std::mem::offset_of!($C, _first_parent) as isize] $P $(: $rest)*
+        );
+    };
+    (@struct_parents $S:ident [$offset:expr] $C:ident) => {};
+
+    //
+    //  Entry point
+    //
+    ($($items:tt)*) => {
+        $crate::poly_hierarchy!(@munch [] [] $($items)*);
+    };
+);
@@ -3,17 +3,20 @@
 //
 //  [Library & Compiler] part
 //
-#![allow(dead_code)]
 
-use alloc::boxed::Box;
-use core::clone;
-use core::fmt;
-use core::intrinsics;
-use core::marker;
-use core::mem;
-
-// KLUDGE
-use std;
+use std::any;
+use std::cell;
+use std::clone;
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+use std::hash;
+use std::mem;
+use std::ptr;
+use std::sync;
+use std::sync::atomic;
+use std::thread;
 
 //
 //  Core Library additions
@@ -21,10 +24,13 @@ use std;
 
 //  Addition to core::clone library
 pub trait RawClone {
-    //  Will write mem::size_of::<Self>() bytes in dst:
-    //  - dst will be overwritten, no destructor will run,
-    //  - dst should be big enough,
-    //  - dst should not overlap with self.
+    /// Will write mem::size_of::<Self>() bytes in dst.
+    ///
+    /// # Safety
+    ///
+    /// - dst will be overwritten, no destructor will run,
+    /// - dst should be big enough, and suitably aligned,
+    /// - dst should not overlap with self.
     unsafe fn raw_clone(&self, dst: *mut u8);
 }
 
@@ -32,35 +38,30 @@ impl<T> RawClone for T
     where T: clone::Clone + Sized
 {
     unsafe fn raw_clone(&self, dst: *mut u8) {
-        use core::ptr;
-
-        let clone = self.clone();
-
-        let src: *const Self = mem::transmute(&clone);
-        let dst: *mut Self = mem::transmute(dst);
-
-        ptr::copy_nonoverlapping(src, dst, 1);
-
-        mem::forget(clone);
+        ptr::write(dst as *mut Self, self.clone());
     }
 }
 
+//  Addition to core::any library
 //
-//  Helpers
+//  The compiler would record the StructInfo in the v-table of any trait object; instead the
+//  struct behind a trait object is queried through this super-trait, which `#[poly_trait]`
+//  adds to any trait it declares.
 //
-//  Humpf, "offsetof" is a reserved identifier but does not do anything :(
-#[macro_export]
-macro_rules! offset_of(
-    ($T:ty, $field:ident) => {
-        unsafe {
-            let exemplar: $T = std::mem::uninitialized();
-            let base: *const u8 = std::mem::transmute(&exemplar);
-            let attr: *const u8 = std::mem::transmute(&exemplar.$field);
-            mem::forget(exemplar);
-            (attr as isize) - (base as isize)
-        }
-    }
-);
+//  Note: v-table pointers cannot be used as keys, as a v-table may be duplicated across
+//  codegen units.
+/// # Safety
+///
+/// poly_struct_id() is the StructId of the concrete type of self.
+pub unsafe trait PolyObject {
+    fn poly_struct_id(&self) -> StructId;
+}
+
+unsafe impl<S> PolyObject for S
+    where S: 'static
+{
+    fn poly_struct_id(&self) -> StructId { struct_id::<S>() }
+}
 
 //
 //  "Manual" intrinsics
@@ -70,224 +71,860 @@ macro_rules! offset_of(
 //  KLUDGEs, specifically, an emulated set of type-infos and v-tables.
 //
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
-pub struct StructId { id: u64 }
+//  The name is only carried along for diagnostics: ids are compared, hashed and ordered
+//  by TypeId alone.
+#[derive(Clone, Copy, Debug)]
+pub struct StructId { id: any::TypeId, name: &'static str }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
-pub struct TraitId { id: u64 }
+#[derive(Clone, Copy, Debug)]
+pub struct TraitId { id: any::TypeId, name: &'static str }
 
 pub fn struct_id<Struct>() -> StructId
-    where Struct: marker::Reflect + 'static
+    where Struct: 'static
 {
-    StructId { id: unsafe { mem::transmute(intrinsics::type_id::<Struct>()) } }
+    StructId { id: any::TypeId::of::<Struct>(), name: any::type_name::<Struct>() }
 }
 
 pub fn trait_id<Trait: ?Sized>() -> TraitId
-    where Trait: marker::Reflect + 'static
+    where Trait: 'static
 {
-    TraitId { id: unsafe { mem::transmute(intrinsics::type_id::<Trait>()) } }
+    TraitId { id: any::TypeId::of::<Trait>(), name: any::type_name::<Trait>() }
 }
 
+macro_rules! id_impls(
+    ($Id:ident) => {
+        impl $Id {
+            //  The name of the type, as per `std::any::type_name`.
+            pub fn name(&self) -> &'static str { self.name }
+        }
+
+        impl PartialEq for $Id {
+            fn eq(&self, other: &$Id) -> bool { self.id == other.id }
+        }
+
+        impl Eq for $Id {}
+
+        impl hash::Hash for $Id {
+            fn hash<H: hash::Hasher>(&self, state: &mut H) { self.id.hash(state) }
+        }
+
+        impl PartialOrd for $Id {
+            fn partial_cmp(&self, other: &$Id) -> Option<cmp::Ordering> { Some(self.cmp(other)) }
+        }
+
+        impl Ord for $Id {
+            fn cmp(&self, other: &$Id) -> cmp::Ordering { self.id.cmp(&other.id) }
+        }
+    }
+);
+
+id_impls!(StructId);
+id_impls!(TraitId);
+
 // KLUDGE
+//
+//  The layout of trait objects is not specified, so rather than picking a v-ptr apart
+//  we record a function re-assembling the trait object from a pointer to its data.
+//
+//  The function is type-erased, its actual type is `fn (*mut ()) -> *mut $T`.
+//
+//  Note: a closure rather than a nested function, so that $S may be a generic parameter.
+#[doc(hidden)]
+#[macro_export]
 macro_rules! make_vptr(
     ($T:ty, $S:ty) => (
         {
-            let s: &$S = unsafe { ::std::mem::uninitialized() };
-            let t: &$T = s;
-            let r: ::std::raw::TraitObject = unsafe { ::std::mem::transmute(t) };
-            r.vtable
+            let make: fn (*mut ()) -> *mut $T = |data| data as *mut $S as *mut $T;
+            make as *const ()
         }
     )
 );
 
 // KLUDGE
+#[doc(hidden)]
 #[macro_export]
 macro_rules! make_vtable(
     ($T:ty, $S:ty) => (
         {
-            $crate::internal::VTable::new::<$T, $S>(
-                make_vptr!($T, $S)
-            )
+            //  Safety: the table is built by make_vptr!($T, $S).
+            unsafe {
+                $crate::VTable::new::<$T, $S>(
+                    $crate::make_vptr!($T, $S)
+                )
+            }
         }
     )
 );
 
 // KLUDGE
-struct StructInfoRegistry {
-    inner: std::sync::Arc<Vec<(StructId, StructInfo)>>,
+//
+//  An append-only registry, mapping ids to type-infos or v-tables.
+//
+//  The registries are first built from the registrations collected at link-time, then may
+//  be extended at any time, for example by a plugin. Entries are leaked, and therefore live
+//  forever.
+//
+//  The link-time entries are frozen, and looked up without locking; the late ones are kept
+//  aside, behind a lock which is only taken once some were registered.
+struct Registry<K, V: 'static> {
+    frozen: IdMap<K, &'static V>,
+    late: sync::RwLock<IdMap<K, &'static V>>,
+    has_late: atomic::AtomicBool,
 }
 
-// KLUDGE
-struct TraitInfoRegistry {
-    inner: std::sync::Arc<Vec<(TraitId, TraitInfo)>>,
+//  The ids are TypeIds, which are hashes already: rather than hashed anew, they are merely
+//  mixed together, for the (TraitId, StructId) pairs of the v-tables.
+type IdMap<K, V> = HashMap<K, V, hash::BuildHasherDefault<IdHasher>>;
+
+#[derive(Default)]
+struct IdHasher { hash: u64 }
+
+impl hash::Hasher for IdHasher {
+    fn finish(&self) -> u64 { self.hash }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ n).wrapping_mul(0x517c_c1b7_2722_0a95);
+    }
 }
 
+type StructInfoRegistry = Registry<StructId, StructInfo>;
+type TraitInfoRegistry = Registry<TraitId, TraitInfo>;
+type VTableRegistry = Registry<VTableRegistryId, VTable>;
+
 // KLUDGE
 pub type VTableRegistryId = (TraitId, StructId);
 pub type VTableRegistryTables = Vec<(VTableRegistryId, Box<[VTable]>)>;
 pub type VTableRegistryIndices = Vec<(VTableRegistryId, VTableRegistryId, isize)>;
 
-struct VTableRegistry {
-    tables: std::sync::Arc<VTableRegistryTables>,
-    indices: std::sync::Arc<VTableRegistryIndices>,
+impl<K, V> Registry<K, V>
+    where K: Copy + Eq + hash::Hash,
+          V: 'static
+{
+    //  The first registration of an id wins, as the registrations are not ordered.
+    fn new(entries: Vec<(K, &'static V)>) -> Registry<K, V> {
+        let mut frozen = IdMap::with_capacity_and_hasher(entries.len(), Default::default());
+
+        for (id, value) in entries {
+            frozen.entry(id).or_insert(value);
+        }
+
+        Registry {
+            frozen,
+            late: sync::RwLock::new(IdMap::default()),
+            has_late: atomic::AtomicBool::new(false),
+        }
+    }
+
+    fn get(&self, id: K) -> Option<&'static V> {
+        if let Some(value) = self.frozen.get(&id) { return Some(*value); }
+
+        if !self.has_late.load(atomic::Ordering::Acquire) { return None; }
+
+        //  The map is only modified once all checks passed, hence is consistent even if poisoned.
+        let late = self.late.read().unwrap_or_else(sync::PoisonError::into_inner);
+        late.get(&id).copied()
+    }
+
+    //  The first of the ids which is already registered, or appears twice.
+    fn find_duplicate<I>(&self, ids: I) -> Option<K>
+        where I: IntoIterator<Item = K>
+    {
+        let late = self.late.read().unwrap_or_else(sync::PoisonError::into_inner);
+        let mut seen = HashSet::new();
+
+        ids.into_iter().find(|id| self.frozen.contains_key(id) || late.contains_key(id) || !seen.insert(*id))
+    }
+
+    fn lock(&self) -> LateEntries<'_, K, V> {
+        LateEntries {
+            late: self.late.write().unwrap_or_else(sync::PoisonError::into_inner),
+            registry: self,
+        }
+    }
+} // impl Registry
+
+//  The late entries of a registry, locked for writing.
+struct LateEntries<'a, K, V: 'static> {
+    late: sync::RwLockWriteGuard<'a, IdMap<K, &'static V>>,
+    registry: &'a Registry<K, V>,
+}
+
+impl<'a, K, V> LateEntries<'a, K, V>
+    where K: Copy + Eq + hash::Hash,
+          V: 'static
+{
+    fn extend(&mut self, entries: Vec<(K, &'static V)>) {
+        if entries.is_empty() { return; }
+
+        self.late.extend(entries);
+        self.registry.has_late.store(true, atomic::Ordering::Release);
+    }
+} // impl LateEntries
+
+//  Leaks the type-infos, so that they live as long as the registry.
+fn leak_all<K, V>(entries: Vec<(K, V)>) -> Vec<(K, &'static V)> {
+    entries.into_iter().map(|(id, value)| (id, &*Box::leak(Box::new(value)))).collect()
+}
+
+//  Lists the v-tables of the blocks, as (id, index of the block, index within the block).
+//
+//  Indices pointing to an unknown block, or outside of their block, are ignored.
+fn v_table_slots(tables: &VTableRegistryTables, indices: &VTableRegistryIndices)
+    -> Vec<(VTableRegistryId, usize, usize)>
+{
+    let mut slots = Vec::with_capacity(tables.len() + indices.len());
+    let mut blocks = HashMap::with_capacity(tables.len());
+
+    for (block, (id, v_tables)) in tables.iter().enumerate() {
+        if !v_tables.is_empty() { slots.push((*id, block, 0)); }
+
+        blocks.entry(*id).or_insert(block);
+    }
+
+    for &(id, head, offset) in indices {
+        let index = offset as usize / mem::size_of::<VTable>();
+
+        if let Some(&block) = blocks.get(&head).filter(|b| index < tables[**b].1.len()) {
+            slots.push((id, block, index));
+        }
+    }
+
+    slots
+}
+
+//  Leaks the v-table blocks, and lists the v-tables of `slots` they contain.
+fn leak_v_tables(tables: VTableRegistryTables, slots: Vec<(VTableRegistryId, usize, usize)>)
+    -> Vec<(VTableRegistryId, &'static VTable)>
+{
+    let blocks: Vec<&'static [VTable]> = tables.into_iter().map(|(_, block)| &*Box::leak(block)).collect();
+
+    slots.into_iter().map(|(id, block, index)| (id, &blocks[block][index])).collect()
 }
 
 // KLUDGE
-static mut STRUCT_INFO_REGISTRY: *const StructInfoRegistry = 0 as *const StructInfoRegistry;
-static mut TRAIT_INFO_REGISTRY: *const TraitInfoRegistry = 0 as *const TraitInfoRegistry;
-static mut VTABLE_REGISTRY: *const VTableRegistry = 0 as *const VTableRegistry;
+//
+//  Each registry is built once, on first use, from the registrations collected at link-time;
+//  OnceLock ensures that its construction happens-before any look-up, from whichever thread.
+static STRUCT_INFO_REGISTRY: sync::OnceLock<StructInfoRegistry> = sync::OnceLock::new();
+static TRAIT_INFO_REGISTRY: sync::OnceLock<TraitInfoRegistry> = sync::OnceLock::new();
+static VTABLE_REGISTRY: sync::OnceLock<VTableRegistry> = sync::OnceLock::new();
+
+thread_local! {
+    static BUILDING_STRUCT_INFO_REGISTRY: cell::Cell<bool> = const { cell::Cell::new(false) };
+    static BUILDING_TRAIT_INFO_REGISTRY: cell::Cell<bool> = const { cell::Cell::new(false) };
+    static BUILDING_VTABLE_REGISTRY: cell::Cell<bool> = const { cell::Cell::new(false) };
+}
+
+//  Late registrations are performed one at a time, see register_all.
+static REGISTERING: sync::Mutex<()> = sync::Mutex::new(());
+
+thread_local! {
+    //  The type-infos of the batch being registered by this thread, visible to its v-tables.
+    static PENDING_STRUCT_INFOS: cell::RefCell<Vec<(StructId, &'static StructInfo)>> =
+        const { cell::RefCell::new(Vec::new()) };
+    static PENDING_TRAIT_INFOS: cell::RefCell<Vec<(TraitId, &'static TraitInfo)>> =
+        const { cell::RefCell::new(Vec::new()) };
+}
+
+fn pending<K, V>(pending: &'static thread::LocalKey<cell::RefCell<Vec<(K, &'static V)>>>, id: K)
+    -> Option<&'static V>
+    where K: Eq,
+          V: 'static
+{
+    pending.with(|p| p.borrow().iter().find(|e| e.0 == id).map(|e| e.1))
+}
 
 // KLUDGE
-pub fn init_struct_info_registry(registry: Vec<(StructId, StructInfo)>) {
-    static ONCE: std::sync::Once = std::sync::ONCE_INIT;
-    unsafe {
-        ONCE.call_once(|| {
-            let registry = StructInfoRegistry { inner: std::sync::Arc::new(registry) };
-            STRUCT_INFO_REGISTRY = mem::transmute(Box::new(registry));
-        });
-    }
+//
+//  The type-infos and v-tables of a module, as generated by `poly_hierarchy!`.
+//
+//  Registrations are submitted from anywhere in the program, dependent crates included,
+//  and collected at link-time; the registries are built from them on first use. Late
+//  registrations, such as those of a plugin, are passed to `register` instead.
+pub struct Registration {
+    struct_info: fn (&mut Vec<(StructId, StructInfo)>),
+    trait_info: fn (&mut Vec<(TraitId, TraitInfo)>),
+    v_tables: fn (&mut VTableRegistryTables, &mut VTableRegistryIndices),
 }
 
+impl Registration {
+    pub const fn new(
+        struct_info: fn (&mut Vec<(StructId, StructInfo)>),
+        trait_info: fn (&mut Vec<(TraitId, TraitInfo)>),
+        v_tables: fn (&mut VTableRegistryTables, &mut VTableRegistryIndices),
+    )
+        -> Registration
+    {
+        Registration { struct_info, trait_info, v_tables }
+    }
+} // impl Registration
+
+inventory::collect!(Registration);
+
 // KLUDGE
-pub fn init_trait_info_registry(registry: Vec<(TraitId, TraitInfo)>) {
-    static ONCE: std::sync::Once = std::sync::ONCE_INIT;
-    unsafe {
-        ONCE.call_once(|| {
-            let registry = TraitInfoRegistry { inner: std::sync::Arc::new(registry) };
-            TRAIT_INFO_REGISTRY = mem::transmute(Box::new(registry));
-        });
+//
+//  Returns the registry, building it first if necessary.
+//
+//  A look-up issued by a registration function while its own registry is being built would
+//  dead-lock; it is reported as RegistryError::NotInitialized instead.
+fn get_or_build<R>(
+    registry: &'static sync::OnceLock<R>,
+    building: &'static thread::LocalKey<cell::Cell<bool>>,
+    build: fn () -> R,
+)
+    -> Result<&'static R, RegistryError>
+{
+    if let Some(r) = registry.get() { return Ok(r); }
+
+    if building.with(|b| b.replace(true)) { return Err(RegistryError::NotInitialized); }
+
+    //  Clears the flag even if a registration function panics.
+    struct Building(&'static thread::LocalKey<cell::Cell<bool>>);
+
+    impl Drop for Building {
+        fn drop(&mut self) { self.0.with(|b| b.set(false)); }
+    }
+
+    let _building = Building(building);
+
+    Ok(registry.get_or_init(build))
+}
+
+//  Each registry is built separately: building the v-tables requires the type-infos.
+fn struct_info_registry() -> Result<&'static StructInfoRegistry, RegistryError> {
+    get_or_build(&STRUCT_INFO_REGISTRY, &BUILDING_STRUCT_INFO_REGISTRY, || {
+        let mut registry = Vec::new();
+
+        for registration in inventory::iter::<Registration> {
+            (registration.struct_info)(&mut registry);
+        }
+
+        Registry::new(leak_all(registry))
+    })
+}
+
+fn trait_info_registry() -> Result<&'static TraitInfoRegistry, RegistryError> {
+    get_or_build(&TRAIT_INFO_REGISTRY, &BUILDING_TRAIT_INFO_REGISTRY, || {
+        let mut registry = Vec::new();
+
+        for registration in inventory::iter::<Registration> {
+            (registration.trait_info)(&mut registry);
+        }
+
+        Registry::new(leak_all(registry))
+    })
+}
+
+fn vtable_registry() -> Result<&'static VTableRegistry, RegistryError> {
+    get_or_build(&VTABLE_REGISTRY, &BUILDING_VTABLE_REGISTRY, || {
+        let mut tables = Vec::new();
+        let mut indices = Vec::new();
+
+        for registration in inventory::iter::<Registration> {
+            (registration.v_tables)(&mut tables, &mut indices);
+        }
+
+        let slots = v_table_slots(&tables, &indices);
+
+        Registry::new(leak_v_tables(tables, slots))
+    })
+}
+
+//
+//  Late registration
+//
+//  Types unknown at link-time, for example those of a plugin, may be registered at any time;
+//  as v-tables refer to type-infos, structs and traits should be registered first.
+//
+
+pub fn register_struct(entry: (StructId, StructInfo)) -> Result<&'static StructInfo, RegistryError> {
+    let id = entry.0;
+
+    register_all(vec!(entry), Vec::new(), |_, _| ())?;
+
+    try_struct_info_by_id(id)
+}
+
+pub fn register_trait(entry: (TraitId, TraitInfo)) -> Result<&'static TraitInfo, RegistryError> {
+    let id = entry.0;
+
+    register_all(Vec::new(), vec!(entry), |_, _| ())?;
+
+    try_trait_info_by_id(id)
+}
+
+pub fn register_v_tables(tables: VTableRegistryTables, indices: VTableRegistryIndices)
+    -> Result<(), RegistryError>
+{
+    register_all(Vec::new(), Vec::new(), |t, i| {
+        t.extend(tables);
+        i.extend(indices);
+    })
+}
+
+//  Registers all the type-infos and v-tables of a module.
+//
+//  Either all of them are registered, or, if any is already registered, none is.
+pub fn register(registration: &Registration) -> Result<(), RegistryError> {
+    let mut struct_infos = Vec::new();
+    (registration.struct_info)(&mut struct_infos);
+
+    let mut trait_infos = Vec::new();
+    (registration.trait_info)(&mut trait_infos);
+
+    register_all(struct_infos, trait_infos, registration.v_tables)
+}
+
+//  The whole batch is checked, then inserted with all three registries locked: readers see
+//  either none or all of it.
+//
+//  The v-tables refer to the type-infos, which are therefore leaked first, and made visible
+//  to the registering thread only while the v-tables are built; should the v-tables turn out
+//  to be already registered, those type-infos remain leaked, though unregistered.
+fn register_all<F>(
+    struct_infos: Vec<(StructId, StructInfo)>,
+    trait_infos: Vec<(TraitId, TraitInfo)>,
+    v_tables: F,
+)
+    -> Result<(), RegistryError>
+    where F: FnOnce(&mut VTableRegistryTables, &mut VTableRegistryIndices)
+{
+    let (struct_registry, trait_registry, vtable_registry) =
+        (struct_info_registry()?, trait_info_registry()?, vtable_registry()?);
+
+    //  No other batch may be registered between the checks and the insertion.
+    let _registering = REGISTERING.lock().unwrap_or_else(sync::PoisonError::into_inner);
+
+    if let Some(id) = struct_registry.find_duplicate(struct_infos.iter().map(|e| e.0)) {
+        return Err(RegistryError::DuplicateStruct(id));
+    }
+
+    if let Some(id) = trait_registry.find_duplicate(trait_infos.iter().map(|e| e.0)) {
+        return Err(RegistryError::DuplicateTrait(id));
+    }
+
+    let struct_infos = leak_all(struct_infos);
+    let trait_infos = leak_all(trait_infos);
+
+    let mut tables = Vec::new();
+    let mut indices = Vec::new();
+
+    {
+        //  Clears the pending type-infos even if the v-tables panic.
+        struct Pending;
+
+        impl Drop for Pending {
+            fn drop(&mut self) {
+                PENDING_STRUCT_INFOS.with(|p| p.borrow_mut().clear());
+                PENDING_TRAIT_INFOS.with(|p| p.borrow_mut().clear());
+            }
+        }
+
+        PENDING_STRUCT_INFOS.with(|p| p.borrow_mut().extend(struct_infos.iter().copied()));
+        PENDING_TRAIT_INFOS.with(|p| p.borrow_mut().extend(trait_infos.iter().copied()));
+
+        let _pending = Pending;
+
+        v_tables(&mut tables, &mut indices);
     }
+
+    let slots = v_table_slots(&tables, &indices);
+
+    if let Some((trait_id, struct_id)) = vtable_registry.find_duplicate(slots.iter().map(|s| s.0)) {
+        return Err(RegistryError::DuplicateImpl { trait_id, struct_id });
+    }
+
+    //  Always locked in this order.
+    let mut structs = struct_registry.lock();
+    let mut traits = trait_registry.lock();
+    let mut v_tables = vtable_registry.lock();
+
+    structs.extend(struct_infos);
+    traits.extend(trait_infos);
+    v_tables.extend(leak_v_tables(tables, slots));
+
+    Ok(())
 }
 
 // KLUDGE
-pub fn init_vtable_registry(tables: VTableRegistryTables, indices: VTableRegistryIndices)
+//
+//  Registry entries for S and T, with the stock v-table getters and dropper.
+pub fn make_struct_info<S>(off: fn (StructId) -> &'static [isize]) -> (StructId, StructInfo)
+    where S: 'static
 {
-    static ONCE: std::sync::Once = std::sync::ONCE_INIT;
-    unsafe {
-        ONCE.call_once(|| {
-            let registry = VTableRegistry {
-                tables: std::sync::Arc::new(tables),
-                indices: std::sync::Arc::new(indices),
-            };
-            VTABLE_REGISTRY = mem::transmute(Box::new(registry));
-        });
+    fn v_table<S>(id: TraitId) -> Option<&'static VTable>
+        where S: 'static
+    {
+        v_table_by_id(id, struct_id::<S>())
+    }
+
+    fn drop<S>(raw: *mut ())
+        where S: 'static
+    {
+        unsafe { ptr::drop_in_place(raw as *mut S); }
+    }
+
+    (
+        struct_id::<S>(),
+        StructInfo::new::<S>(v_table::<S>, off, drop::<S>)
+    )
+}
+
+pub fn make_trait_info<T: ?Sized>() -> (TraitId, TraitInfo)
+    where T: 'static
+{
+    fn v_table<T: ?Sized>(id: StructId) -> Option<&'static VTable>
+        where T: 'static
+    {
+        v_table_by_id(trait_id::<T>(), id)
     }
+
+    (
+        trait_id::<T>(),
+        TraitInfo::new::<T>(v_table::<T>)
+    )
 }
 
+//  Registers the TraitInfo of T alone, as submitted by `#[poly_trait]`.
+pub const fn trait_registration<T: ?Sized>() -> Registration
+    where T: 'static
+{
+    fn no_struct_info(_: &mut Vec<(StructId, StructInfo)>) {}
+
+    fn trait_info<T: ?Sized>(collector: &mut Vec<(TraitId, TraitInfo)>)
+        where T: 'static
+    {
+        collector.push(make_trait_info::<T>());
+    }
+
+    fn no_v_tables(_: &mut VTableRegistryTables, _: &mut VTableRegistryIndices) {}
+
+    Registration::new(no_struct_info, trait_info::<T>, no_v_tables)
+}
+
+//  Errors reported by the fallible registry lookups.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RegistryError {
+    //  The registry is not available (yet), for example while it is being built.
+    NotInitialized,
+    //  No StructInfo was registered for this struct.
+    UnknownStruct(StructId),
+    //  No TraitInfo was registered for this trait.
+    UnknownTrait(TraitId),
+    //  No v-table was registered for this (trait, struct) pair.
+    MissingImpl { trait_id: TraitId, struct_id: StructId },
+    //  A StructInfo was already registered for this struct.
+    DuplicateStruct(StructId),
+    //  A TraitInfo was already registered for this trait.
+    DuplicateTrait(TraitId),
+    //  A v-table was already registered for this (trait, struct) pair.
+    DuplicateImpl { trait_id: TraitId, struct_id: StructId },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+        RegistryError::NotInitialized =>
+            write!(formatter, "registry accessed before being initialized"),
+        RegistryError::UnknownStruct(id) =>
+            write!(formatter, "no struct_info registered for {}", id.name()),
+        RegistryError::UnknownTrait(id) =>
+            write!(formatter, "no trait_info registered for {}", id.name()),
+        RegistryError::MissingImpl { trait_id, struct_id } =>
+            write!(formatter, "no v_table registered for {} implementing {}", struct_id.name(), trait_id.name()),
+        RegistryError::DuplicateStruct(id) =>
+            write!(formatter, "a struct_info is already registered for {}", id.name()),
+        RegistryError::DuplicateTrait(id) =>
+            write!(formatter, "a trait_info is already registered for {}", id.name()),
+        RegistryError::DuplicateImpl { trait_id, struct_id } =>
+            write!(formatter, "a v_table is already registered for {} implementing {}", struct_id.name(), trait_id.name()),
+        }
+    }
+} // impl Display for RegistryError
+
+impl error::Error for RegistryError {}
+
 pub fn struct_info<Struct>() -> &'static StructInfo
-    where Struct: marker::Reflect + 'static
+    where Struct: 'static
 {
     let struct_id = struct_id::<Struct>();
     struct_info_by_id(struct_id)
 }
 
 pub fn struct_info_by_id(struct_id: StructId) -> &'static StructInfo {
-    unsafe {
-        // This function is supposed to be an intrinsic substituted by the compiler,
-        // so I won't be thinking too hard about race conditions...
-        if STRUCT_INFO_REGISTRY.is_null() {
-            panic!("Call init_struct_info_registry before the first call to struct_info.")
-        }
+    try_struct_info_by_id(struct_id).unwrap_or_else(|e| panic!("{}", e))
+}
 
-        for &(s_id, ref struct_info) in &*(*STRUCT_INFO_REGISTRY).inner {
-            if s_id == struct_id { return &struct_info; }
-        }
+pub fn try_struct_info<Struct>() -> Result<&'static StructInfo, RegistryError>
+    where Struct: 'static
+{
+    try_struct_info_by_id(struct_id::<Struct>())
+}
 
-        panic!("No such struct_info registered.");
-    }
+pub fn try_struct_info_by_id(struct_id: StructId) -> Result<&'static StructInfo, RegistryError> {
+    struct_info_registry()?.get(struct_id)
+        .or_else(|| pending(&PENDING_STRUCT_INFOS, struct_id))
+        .ok_or(RegistryError::UnknownStruct(struct_id))
 }
 
 pub fn trait_info<Trait: ?Sized>() -> &'static TraitInfo
-    where Trait: marker::Reflect + 'static
+    where Trait: 'static
 {
     let trait_id = trait_id::<Trait>();
     trait_info_by_id(trait_id)
 }
 
 pub fn trait_info_by_id(trait_id: TraitId) -> &'static TraitInfo {
-    unsafe {
-        // This function is supposed to be an intrinsic substituted by the compiler,
-        // so I won't be thinking too hard about race conditions...
-        if TRAIT_INFO_REGISTRY.is_null() {
-            panic!("Call init_trait_info_registry before the first call to trait_info.")
-        }
+    try_trait_info_by_id(trait_id).unwrap_or_else(|e| panic!("{}", e))
+}
 
-        for &(t_id, ref trait_info) in &*(*TRAIT_INFO_REGISTRY).inner {
-            if t_id == trait_id { return &trait_info; }
-        }
+pub fn try_trait_info<Trait: ?Sized>() -> Result<&'static TraitInfo, RegistryError>
+    where Trait: 'static
+{
+    try_trait_info_by_id(trait_id::<Trait>())
+}
 
-        panic!("No such trait_info registered.");
-    }
+pub fn try_trait_info_by_id(trait_id: TraitId) -> Result<&'static TraitInfo, RegistryError> {
+    trait_info_registry()?.get(trait_id)
+        .or_else(|| pending(&PENDING_TRAIT_INFOS, trait_id))
+        .ok_or(RegistryError::UnknownTrait(trait_id))
 }
 
 pub fn v_table<Trait: ?Sized, Struct>() -> &'static VTable
-    where Trait: marker::Reflect + 'static,
-          Struct: ExtendTrait<Trait> + marker::Reflect + 'static
+    where Trait: 'static,
+          Struct: ExtendTrait<Trait> + 'static
 {
-    let trait_id = trait_id::<Trait>();
-    let struct_id = struct_id::<Struct>();
-    v_table_by_id(trait_id, struct_id).unwrap()
+    try_v_table::<Trait, Struct>().unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_v_table<Trait: ?Sized, Struct>() -> Result<&'static VTable, RegistryError>
+    where Trait: 'static,
+          Struct: 'static
+{
+    try_v_table_by_id(trait_id::<Trait>(), struct_id::<Struct>())
+}
+
+//  The v-table of (T, S), S being the struct behind `it`.
+pub fn v_table_of<Trait: ?Sized>(it: &Trait) -> &'static VTable
+    where Trait: PolyObject + 'static
+{
+    try_v_table_of(it).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_v_table_of<Trait: ?Sized>(it: &Trait) -> Result<&'static VTable, RegistryError>
+    where Trait: PolyObject + 'static
+{
+    try_v_table_by_id(trait_id::<Trait>(), it.poly_struct_id())
 }
 
 pub fn v_table_by_id(trait_id: TraitId, struct_id: StructId) -> Option<&'static VTable> {
-    type Id = VTableRegistryId;
+    try_v_table_by_id(trait_id, struct_id).ok()
+}
 
-    fn locate_index(registry: &'static VTableRegistry, id: Id) -> Option<(Id, isize)> {
-        for &(k_id, v_id, off) in &*registry.indices {
-            if k_id == id { return Some((v_id, off)); }
-        }
+pub fn try_v_table_by_id(trait_id: TraitId, struct_id: StructId)
+    -> Result<&'static VTable, RegistryError>
+{
+    vtable_registry()?.get((trait_id, struct_id)).ok_or(RegistryError::MissingImpl { trait_id, struct_id })
+} // try_v_table_by_id
 
-        None
-    }
 
-    fn locate_vtable(registry: &'static VTableRegistry, id: Id, offset: isize) -> Option<&'static VTable> {
-        for &(k_id, ref array) in &*registry.tables {
-            if k_id != id { continue; }
+//
+//  "Manual" marker traits
+//
+//  Those intrinsics should be automatically implemented by the compiler, based on the traits and types properties.
+//
 
-            return Some(unsafe {
-                let base: *const u8 = mem::transmute(array.get_unchecked(0));
-                let v_table: &'static VTable = mem::transmute(base.offset(offset));
-                v_table
-            });
-        }
+/// # Safety
+///
+/// Self implements T, and a v-table for (T, Self) is registered.
+pub unsafe trait ExtendTrait<T: ?Sized> { }
 
-        None
-    }
+/// # Safety
+///
+/// offsets() lists, in increasing order, the offsets of each T sub-object within Self.
+pub unsafe trait ExtendStruct<T> { fn offsets() -> &'static [isize]; }
+
+/// # Safety
+///
+/// A v-table of Self may stand in for a v-table of T, see VTable::as_trait_ptr.
+pub unsafe trait FirstExtendTrait<T: ?Sized>: ExtendTrait<T> {}
 
-    let registry: &'static VTableRegistry = unsafe {
-        // This function is supposed to be an intrinsic substituted by the compiler,
-        // so I won't be thinking too hard about race conditions...
-        if VTABLE_REGISTRY.is_null() {
-            panic!("Call init_vtable_registry before the first call to v_table.")
+/// # Safety
+///
+/// Self contains one and only one T sub-object: offsets() has a single element.
+pub unsafe trait UniqueExtendStruct<T>: ExtendStruct<T> {}
+
+/// # Safety
+///
+/// The one and only T sub-object of Self lives at offset 0.
+pub unsafe trait FirstExtendStruct<T>: UniqueExtendStruct<T> {}
+
+/// # Safety
+///
+/// offset() is the offset, in bytes, of the v-table of T within the v-table block of Self.
+pub unsafe trait TraitExtendTrait<T: ?Sized>: ExtendTrait<T> { fn offset() -> isize; }
+
+//  `()` is the common ancestor of all structs, itself included; see `#[derive(PolyStruct)]`
+//  for the others.
+unsafe impl ExtendStruct<()> for () { fn offsets() -> &'static [isize] { &[0] } }
+unsafe impl UniqueExtendStruct<()> for () {}
+unsafe impl FirstExtendStruct<()> for () {}
+
+
+//
+//  "Manual" struct layout
+//
+//  The compiler would know the offsets of all the sub-objects of a struct, instead they
+//  are collected at run-time, see `#[derive(PolyStruct)]` and `poly_hierarchy!`.
+//
+
+/// # Safety
+///
+/// sub_objects() lists Self and each of its (transitive) parents, at their actual offset.
+pub unsafe trait PolyStruct: 'static {
+    //  Pushes (struct_id, base + offset) for Self, then recursively for each parent.
+    fn sub_objects(collector: &mut Vec<(StructId, isize)>, base: isize);
+
+    //  The sorted offsets of each sub-object identified by `id`, suitable for StructInfo.
+    fn offsets(id: StructId) -> &'static [isize];
+}
+
+//  Sorts the offsets of ExtendStruct::offsets, at compile-time.
+pub const fn sort_offsets<const N: usize>(mut offsets: [isize; N]) -> [isize; N] {
+    let mut i = 1;
+    while i < N {
+        let mut j = i;
+        while j > 0 && offsets[j - 1] > offsets[j] {
+            let tmp = offsets[j];
+            offsets[j] = offsets[j - 1];
+            offsets[j - 1] = tmp;
+            j -= 1;
         }
+        i += 1;
+    }
+    offsets
+}
+
+//  Implementation detail of `#[derive(PolyStruct)]`: invokes the next `__poly_extend_X!`
+//  macro of the list, or, once the list is exhausted, `__poly_extend_struct!` with the
+//  collected ancestors of $C.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __poly_next(
+    ($C:ty; [($($m:ident)::+, [$P:ty], [$off:expr], $first:tt) $($todo:tt)*]; [$($acc:tt)*]) => {
+        $($m)::+ ! { @collect $C; [$P]; [$off]; $first; [$($todo)*]; [$($acc)*] }
+    };
+    ($C:ty; []; [$($acc:tt)*]) => {
+        $crate::__poly_extend_struct! { $C; $($acc)* }
+    };
+);
 
-        &*VTABLE_REGISTRY
+//  Implementation detail of `#[poly_trait]`: invokes the next `__poly_trait_X!` macro of
+//  the list, or, once the list is exhausted, `__poly_extend_trait!` with the collected
+//  ancestors of $X.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __poly_trait_next(
+    ($X:ty; [($($m:ident)::+, [$T:path]) $($todo:tt)*]; [$($acc:tt)*]) => {
+        $($m)::+ ! { @collect $X; [$T]; [$($todo)*]; [$($acc)*] }
     };
+    ($X:ty; []; [$($acc:tt)*]) => {
+        $crate::__poly_extend_trait! { $X; $($acc)* }
+    };
+);
 
-    let key = (trait_id, struct_id);
+//  The storage behind PolyStruct::offsets, one static per struct.
+pub struct OffsetsCache {
+    inner: sync::OnceLock<Vec<(StructId, Box<[isize]>)>>,
+}
+
+impl OffsetsCache {
+    pub const fn new() -> OffsetsCache { OffsetsCache { inner: sync::OnceLock::new() } }
+
+    pub fn offsets<S>(&'static self, id: StructId) -> &'static [isize]
+        where S: PolyStruct
+    {
+        //  The common ancestor is at the start of any struct.
+        if id == struct_id::<()>() { return &[0]; }
+
+        let all = self.inner.get_or_init(|| {
+            let mut sub_objects = Vec::new();
+            S::sub_objects(&mut sub_objects, 0);
+            sub_objects.sort();
+
+            let mut result: Vec<(StructId, Vec<isize>)> = Vec::new();
+            for (id, offset) in sub_objects {
+                match result.last_mut() {
+                Some(&mut (last, ref mut offsets)) if last == id => offsets.push(offset),
+                _ => result.push((id, vec!(offset))),
+                }
+            }
 
-    match locate_index(registry, key) {
-    Some((id, offset)) => locate_vtable(registry, id, offset),
-    None               => locate_vtable(registry, key, 0),
+            result.into_iter().map(|(id, offsets)| (id, offsets.into_boxed_slice())).collect()
+        });
+
+        for (s_id, offsets) in all {
+            if *s_id == id { return offsets; }
+        }
+
+        &[]
     }
-} // v_table_by_id
+} // impl OffsetsCache
+
+impl Default for OffsetsCache {
+    fn default() -> OffsetsCache { OffsetsCache::new() }
+}
 
 
 //
-//  "Manual" marker traits
+//  "Manual" v-table layout
 //
-//  Those intrinsics should be automatically implemented by the compiler, based on the traits and types properties.
+//  The compiler would lay out the v-table block of a trait, instead it is computed at
+//  run-time from its super-traits, see `#[poly_trait]`.
 //
-pub unsafe trait ExtendTrait<T: ?Sized> { }
-pub unsafe trait ExtendStruct<T> { fn offsets() -> &'static [isize]; }
-pub unsafe trait FirstExtendTrait<T: ?Sized>: ExtendTrait<T> {}
-pub unsafe trait FirstExtendStruct<T>: ExtendStruct<T> {}
-pub unsafe trait TraitExtendTrait<T: ?Sized>: ExtendTrait<T> { fn offset() -> isize; }
+
+/// # Safety
+///
+/// block() lists the traits of the v-table block of Self, which is registered accordingly.
+pub unsafe trait PolyTrait: 'static {
+    //  Pushes Self, then recursively the block of each of its super-traits.
+    fn collect_traits(collector: &mut Vec<TraitId>);
+
+    //  The traits of the v-table block: Self first, then the block of each super-trait.
+    //
+    //  The block of every ancestor is thus a contiguous part of the block of Self, whichever
+    //  block Self is itself part of, so that up-casting is a matter of a fixed offset; an
+    //  ancestor reached through several super-traits appears once per path.
+    fn block() -> &'static [TraitId];
+}
+
+//  Offset, in bytes, of the first v-table of B within the v-table block of T.
+pub fn trait_slot<T: ?Sized, B: ?Sized>() -> isize
+    where T: PolyTrait,
+          B: 'static,
+{
+    let index = T::block().iter().position(|id| *id == trait_id::<B>())
+        .expect("No such super-trait in the v-table block.");
+
+    (index * mem::size_of::<VTable>()) as isize
+}
 
 
 //
 //  Raw representation of type info data in ROM.
 //
+//  Being in ROM, type infos and v-tables are immutable and shared freely across threads:
+//  StructInfo, TraitInfo and VTable are all Send and Sync. StructInfo and TraitInfo are
+//  so automatically, being made of ids and function pointers; VTable is so manually.
+//
 #[repr(C)]
 pub struct StructInfo {
     size_align: u64,        // high 8 bits: log2(align), low 56 bits: size
@@ -307,7 +944,7 @@ pub struct TraitInfo {
 pub struct VTable {
     struct_info: &'static StructInfo,
     trait_info: &'static TraitInfo,
-    table: *mut (),         // KLUDGE
+    table: *const (),       // KLUDGE: see make_vptr!
 }
 
 impl StructInfo {
@@ -319,7 +956,7 @@ impl StructInfo {
         off: fn (StructId) -> &'static [isize],
         drop: fn (*mut ()) -> ()
     ) -> StructInfo
-        where S: marker::Reflect + 'static
+        where S: 'static
     {
         fn log2(n: u64) -> u64 {
             let mut n = n;
@@ -376,7 +1013,7 @@ impl fmt::Debug for StructInfo {
 
 impl TraitInfo {
     pub fn new<T: ?Sized>(vt: fn (StructId) -> Option<&'static VTable>) -> TraitInfo
-        where T: marker::Reflect + 'static
+        where T: 'static
     {
         TraitInfo {
             trait_id: trait_id::<T>(),
@@ -401,15 +1038,31 @@ impl fmt::Debug for TraitInfo {
     }
 } // impl Debug for TraitInfo
 
+//  The v-table itself is immutable, and `table` is a plain function pointer.
+unsafe impl Send for VTable {}
+unsafe impl Sync for VTable {}
+
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<StructInfo>();
+    assert_send_sync::<TraitInfo>();
+    assert_send_sync::<VTable>();
+};
+
 impl VTable {
-    pub fn new<T: ?Sized, S>(table: *mut ()) -> VTable
-        where T: marker::Reflect + 'static,
-              S: ExtendTrait<T> + marker::Reflect + 'static,
+    /// # Safety
+    ///
+    /// table is a `fn (*mut ()) -> *mut T` re-assembling a T from a pointer to an S, as
+    /// built by make_vptr!(T, S).
+    pub unsafe fn new<T: ?Sized, S>(table: *const ()) -> VTable
+        where T: 'static,
+              S: 'static,
     {
         VTable {
             struct_info: struct_info::<S>(),
             trait_info: trait_info::<T>(),
-            table: table,
+            table,
         }
     }
 
@@ -417,18 +1070,36 @@ impl VTable {
 
     pub fn trait_info(&self) -> &'static TraitInfo { self.trait_info }
 
-    pub fn table(&self) -> *mut () { self.table }
+    pub fn table(&self) -> *const () { self.table }
+
+    //  Re-assembles a trait object from a pointer to the (most derived) struct.
+    //
+    //  A lightweight up-cast (see FirstExtendTrait) may leave us with the v-table of
+    //  a derived trait, in which case the v-table of T is looked up instead.
+    pub fn as_trait_ptr<T: ?Sized>(&self, data: *mut ()) -> *mut T
+        where T: 'static,
+    {
+        let v_table = if self.trait_info.trait_id == trait_id::<T>() {
+            self
+        } else {
+            self.cast_to_trait::<T>().unwrap_or_else(|e| panic!("{}", e))
+        };
+
+        //  Safety: v_table.table was built by make_vptr!(T, _), as attested by the trait_id.
+        let make = unsafe { mem::transmute::<*const (), fn (*mut ()) -> *mut T>(v_table.table) };
+        make(data)
+    }
 
-    pub fn cast_to_trait<T: ?Sized>(&self) -> Option<&'static VTable>
-        where T: marker::Reflect + 'static,
+    pub fn cast_to_trait<T: ?Sized>(&self) -> Result<&'static VTable, RegistryError>
+        where T: 'static,
     {
-        let trait_info = trait_info::<T>();
+        let trait_info = try_trait_info::<T>()?;
         let struct_info = self.struct_info;
 
         if let Some(vt) = trait_info.v_table(struct_info.struct_id) {
-            Some(vt)
+            Ok(vt)
         } else {
-            struct_info.v_table(trait_info.trait_id)
+            try_v_table_by_id(trait_info.trait_id, struct_info.struct_id)
         }
     }
 } // impl VTable
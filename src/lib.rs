@@ -0,0 +1,61 @@
+//
+//  A tentative implementation of RFC: Disjoint Polymorphism
+//
+//  The crate is split in three parts:
+//
+//  - `internal`: the [Library & Compiler] part, that is the intrinsics, marker traits
+//    and type-info registries that the compiler would normally provide,
+//  - `rtti`: the [Library] part, that is the thin and fat pointers and the casts
+//    built on top of those intrinsics,
+//  - `poly_hierarchy!`, `#[derive(PolyStruct)]` and `#[poly_trait]`: stand-ins for the compiler,
+//    generating the intrinsics of a hierarchy of traits and structs.
+//
+//  The public API consists of the items re-exported at the root of the crate; `__private`
+//  only supports the code generated by the macros, and may change at any time.
+//
+
+//  `T: ?Sized` is spelled in the generic parameters, all other bounds in the where clause.
+#![allow(clippy::multiple_bound_locations)]
+
+//  The code generated by poly-derive refers to `::poly`.
+extern crate self as poly;
+
+#[macro_use]
+mod internal;
+#[macro_use]
+pub mod rtti;
+#[macro_use]
+mod hierarchy;
+
+//  [Library] part
+pub use rtti::{BoxDyn, Class, Dyn, DynClass, DynRef, DynRefMut, SubObjects, UntypedVRef, VRef};
+pub use rtti::{ArcDyn, RcDyn, WeakArcDyn, WeakRcDyn};
+pub use rtti::{Cast, CastError, CastPath, DownCast, DownCastRef, UpCast, UpCastRef};
+
+//  [Library & Compiler] part
+pub use internal::{PolyObject, PolyStruct, PolyTrait, RawClone};
+pub use internal::{ExtendStruct, ExtendTrait, FirstExtendStruct, FirstExtendTrait, TraitExtendTrait};
+pub use internal::UniqueExtendStruct;
+pub use internal::{RegistryError, StructId, StructInfo, TraitId, TraitInfo, VTable};
+pub use internal::{struct_id, struct_info, trait_id, trait_info, v_table};
+pub use internal::{try_struct_info, try_trait_info, try_v_table};
+pub use internal::{try_v_table_of, v_table_of};
+pub use internal::{struct_info_by_id, trait_info_by_id, v_table_by_id};
+pub use internal::{try_struct_info_by_id, try_trait_info_by_id, try_v_table_by_id};
+pub use internal::{Registration, register, register_struct, register_trait, register_v_tables};
+
+//  [Compiler] part
+pub use poly_derive::{PolyStruct, poly_trait};
+
+#[doc(hidden)]
+pub use poly_derive::{__poly_declare_trait, __poly_extend_struct, __poly_extend_trait, __poly_struct_traits};
+
+//  Support for the code generated by `poly_hierarchy!`, `#[derive(PolyStruct)]` and
+//  `#[poly_trait]`.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::internal::{OffsetsCache, VTableRegistryId, VTableRegistryIndices, VTableRegistryTables};
+    pub use crate::internal::{make_struct_info, make_trait_info, sort_offsets, trait_registration, trait_slot};
+
+    pub use inventory;
+}
@@ -3,25 +3,26 @@
 //
 //  [Library] part
 //
-#![allow(dead_code)]
-#![allow(drop_with_repr_extern)]
 
-use alloc::boxed::Box;
-use core::clone;
-use core::convert;
-use core::fmt;
-use core::marker;
-use core::mem;
-use core::ops;
-use core::raw;
-use core::result::Result;
-
-use std;
-
-use internal::RawClone;
-use internal::{ExtendStruct, ExtendTrait, FirstExtendStruct, FirstExtendTrait, TraitExtendTrait};
-use internal::{StructInfo, TraitInfo, VTable};
-use internal::{struct_id, trait_id, v_table};
+use std::alloc;
+use std::cell;
+use std::clone;
+use std::cmp;
+use std::convert;
+use std::error;
+use std::fmt;
+use std::marker;
+use std::mem;
+use std::ops;
+use std::ptr;
+use std::slice;
+use std::sync::atomic;
+
+use crate::internal::{PolyObject, RawClone};
+use crate::internal::{ExtendStruct, ExtendTrait, FirstExtendStruct, FirstExtendTrait, TraitExtendTrait};
+use crate::internal::UniqueExtendStruct;
+use crate::internal::{RegistryError, StructInfo, TraitInfo, VTable};
+use crate::internal::{struct_id, trait_id, try_v_table_of, v_table, v_table_of};
 
 
 //
@@ -36,9 +37,13 @@ pub trait UpCastRef<Target> {
     fn up_cast_ref_mut(&mut self) -> &mut Target;
 }
 
-pub trait DownCast<Target> {
-    fn down_cast(self) -> Result<Target, Self>;
+//  On failure, down_cast and cast hand self back, alongside the reason.
+pub trait DownCast<Target>: Sized {
+    fn down_cast(self) -> Result<Target, (Self, CastError)>;
 
+    /// # Safety
+    ///
+    /// self must actually be a Target, as down_cast would attest.
     unsafe fn unchecked_down_cast(self) -> Target;
 }
 
@@ -46,16 +51,76 @@ pub trait DownCastRef<Target> {
     fn down_cast_ref(&self) -> Option<&Target>;
     fn down_cast_ref_mut(&mut self) -> Option<&mut Target>;
 
+    /// # Safety
+    ///
+    /// self must actually be a Target, as down_cast_ref would attest.
     unsafe fn unchecked_down_cast_ref(&self) -> &Target;
+
+    /// # Safety
+    ///
+    /// self must actually be a Target, as down_cast_ref_mut would attest.
     unsafe fn unchecked_down_cast_ref_mut(&mut self) -> &mut Target;
 }
 
-pub trait Cast<Target> {
-    fn cast(self) -> Result<Target, Self>;
+pub trait Cast<Target>: Sized {
+    fn cast(self) -> Result<Target, (Self, CastError)>;
 
+    /// # Safety
+    ///
+    /// self must actually be a Target, as cast would attest.
     unsafe fn unchecked_cast(self) -> Target;
 }
 
+//  A path from S to one of its sub-objects: a tuple of struct types, each being the one and
+//  only parent of that kind of the previous one, the last being the Target.
+//
+//  For example, `(Right, Base)` picks the Base within the Right within S, even if S contains
+//  another Base elsewhere.
+/// # Safety
+///
+/// offset() is the offset, in bytes, of the Target sub-object within S.
+pub unsafe trait CastPath<S> {
+    type Target: 'static;
+
+    fn offset() -> isize;
+}
+
+fn unique_offset<S, P>() -> isize
+    where S: UniqueExtendStruct<P>,
+{
+    <S as ExtendStruct<P>>::offsets()[0]
+}
+
+macro_rules! impl_cast_path(
+    ($A:ident) => {
+        unsafe impl<S, $A> CastPath<S> for ($A,)
+            where S: UniqueExtendStruct<$A>,
+                  $A: 'static,
+        {
+            type Target = $A;
+
+            fn offset() -> isize { unique_offset::<S, $A>() }
+        }
+    };
+    ($A:ident, $($R:ident),+) => {
+        unsafe impl<S, $A, $($R),+> CastPath<S> for ($A, $($R),+)
+            where S: UniqueExtendStruct<$A>,
+                  $A: 'static,
+                  ($($R,)+): CastPath<$A>,
+        {
+            type Target = <($($R,)+) as CastPath<$A>>::Target;
+
+            fn offset() -> isize {
+                unique_offset::<S, $A>() + <($($R,)+) as CastPath<$A>>::offset()
+            }
+        }
+
+        impl_cast_path!($($R),+);
+    };
+);
+
+impl_cast_path!(A, B, C, D, E, F, G, H);
+
 #[macro_export]
 macro_rules! up_cast(
     ($t:expr => ref mut $T:ty) => { { let tmp: &mut $T = $t.up_cast_ref_mut(); tmp } };
@@ -75,14 +140,177 @@ macro_rules! cast(
     ($t:expr => $T:ty) => { { let tmp: Result<$T, _> = $t.cast(); tmp } };
 );
 
+//  Errors reported by the fallible casts which need to tell why they failed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CastError {
+    //  The object does not contain the target struct, or does not implement the target trait.
+    Mismatch,
+    //  The object contains several target structs, see up_cast_path and sub_objects.
+    Ambiguous { candidates: usize },
+    //  The registries could not tell, for example as the target trait is not registered.
+    Registry(RegistryError),
+}
+
+impl fmt::Display for CastError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+        CastError::Mismatch =>
+            write!(formatter, "no such sub-object or trait"),
+        CastError::Ambiguous { candidates } =>
+            write!(formatter, "ambiguous cast, {} candidate sub-objects", candidates),
+        CastError::Registry(ref error) =>
+            write!(formatter, "cast failed: {}", error),
+        }
+    }
+} // impl Display for CastError
+
+impl error::Error for CastError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+        CastError::Registry(ref error) => Some(error),
+        _ => None,
+        }
+    }
+}
+
+//  A missing v-table is the genuine negative: the struct does not implement the trait.
+impl From<RegistryError> for CastError {
+    fn from(error: RegistryError) -> CastError {
+        match error {
+        RegistryError::MissingImpl { .. } => CastError::Mismatch,
+        other => CastError::Registry(other),
+        }
+    }
+}
+
 //  If Cast is restricted to cross-casts, then CastRef makes little sense...
 //  ... to have a shareable v-ptr, two traits must be related.
 
 //
 //  &T and &mut T
 //
-//  FIXME: once &T and &mut T are wired into VTable, implement UpCast/DownCast/Cast
+//  The v-table of the struct behind a plain trait object is looked up through PolyObject,
+//  after which casting is as for VRef; the data pointer is that of the most derived struct.
 //
+fn up_cast_v_table<T: ?Sized, B: ?Sized>(it: &T) -> &'static VTable
+    where T: TraitExtendTrait<B> + PolyObject + 'static,
+          B: 'static,
+{
+    UntypedVRef::new(v_table_of(it)).up_cast::<T, B>().v_table()
+}
+
+//  The v-table of `it` itself should be registered, whether or not the cast succeeds.
+fn down_cast_v_table<T: ?Sized, D: ?Sized>(it: &T) -> Result<&'static VTable, CastError>
+    where T: PolyObject + 'static,
+          D: TraitExtendTrait<T> + 'static,
+{
+    let v_ref = UntypedVRef::new(try_v_table_of(it).map_err(CastError::Registry)?);
+
+    v_ref.down_cast::<T, D>().map(|r| r.v_table())
+}
+
+fn cast_v_table<T: ?Sized, X: ?Sized>(it: &T) -> Result<&'static VTable, CastError>
+    where T: PolyObject + 'static,
+          X: 'static,
+{
+    let v_ref = UntypedVRef::new(try_v_table_of(it).map_err(CastError::Registry)?);
+
+    v_ref.cast::<T, X>().map(|r| r.v_table())
+}
+
+impl<'a, T: ?Sized, B: ?Sized> UpCast<&'a B> for &'a T
+    where T: TraitExtendTrait<B> + PolyObject + 'static,
+          B: 'static,
+{
+    fn up_cast(self) -> &'a B {
+        let v_table = up_cast_v_table::<T, B>(self);
+
+        unsafe { &*v_table.as_trait_ptr::<B>(self as *const T as *mut ()) }
+    }
+}
+
+impl<'a, T: ?Sized, B: ?Sized> UpCast<&'a mut B> for &'a mut T
+    where T: TraitExtendTrait<B> + PolyObject + 'static,
+          B: 'static,
+{
+    fn up_cast(self) -> &'a mut B {
+        let v_table = up_cast_v_table::<T, B>(self);
+
+        unsafe { &mut *v_table.as_trait_ptr::<B>(self as *mut T as *mut ()) }
+    }
+}
+
+impl<'a, T: ?Sized, D: ?Sized> DownCast<&'a D> for &'a T
+    where T: PolyObject + 'static,
+          D: TraitExtendTrait<T> + 'static,
+{
+    fn down_cast(self) -> Result<&'a D, (&'a T, CastError)> {
+        match down_cast_v_table::<T, D>(self) {
+        Ok(v_table) => Ok(unsafe { &*v_table.as_trait_ptr::<D>(self as *const T as *mut ()) }),
+        Err(error) => Err((self, error)),
+        }
+    }
+
+    unsafe fn unchecked_down_cast(self) -> &'a D {
+        let v_table = down_cast_v_table::<T, D>(self).unwrap();
+
+        &*v_table.as_trait_ptr::<D>(self as *const T as *mut ())
+    }
+}
+
+impl<'a, T: ?Sized, D: ?Sized> DownCast<&'a mut D> for &'a mut T
+    where T: PolyObject + 'static,
+          D: TraitExtendTrait<T> + 'static,
+{
+    fn down_cast(self) -> Result<&'a mut D, (&'a mut T, CastError)> {
+        match down_cast_v_table::<T, D>(self) {
+        Ok(v_table) => Ok(unsafe { &mut *v_table.as_trait_ptr::<D>(self as *mut T as *mut ()) }),
+        Err(error) => Err((self, error)),
+        }
+    }
+
+    unsafe fn unchecked_down_cast(self) -> &'a mut D {
+        let v_table = down_cast_v_table::<T, D>(self).unwrap();
+
+        &mut *v_table.as_trait_ptr::<D>(self as *mut T as *mut ())
+    }
+}
+
+impl<'a, T: ?Sized, X: ?Sized> Cast<&'a X> for &'a T
+    where T: PolyObject + 'static,
+          X: 'static,
+{
+    fn cast(self) -> Result<&'a X, (&'a T, CastError)> {
+        match cast_v_table::<T, X>(self) {
+        Ok(v_table) => Ok(unsafe { &*v_table.as_trait_ptr::<X>(self as *const T as *mut ()) }),
+        Err(error) => Err((self, error)),
+        }
+    }
+
+    unsafe fn unchecked_cast(self) -> &'a X {
+        let v_table = cast_v_table::<T, X>(self).unwrap();
+
+        &*v_table.as_trait_ptr::<X>(self as *const T as *mut ())
+    }
+}
+
+impl<'a, T: ?Sized, X: ?Sized> Cast<&'a mut X> for &'a mut T
+    where T: PolyObject + 'static,
+          X: 'static,
+{
+    fn cast(self) -> Result<&'a mut X, (&'a mut T, CastError)> {
+        match cast_v_table::<T, X>(self) {
+        Ok(v_table) => Ok(unsafe { &mut *v_table.as_trait_ptr::<X>(self as *mut T as *mut ()) }),
+        Err(error) => Err((self, error)),
+        }
+    }
+
+    unsafe fn unchecked_cast(self) -> &'a mut X {
+        let v_table = cast_v_table::<T, X>(self).unwrap();
+
+        &mut *v_table.as_trait_ptr::<X>(self as *mut T as *mut ())
+    }
+}
 
 //
 //  Bricks
@@ -95,7 +323,7 @@ pub struct UntypedVRef {
 
 #[repr(C)]
 pub struct VRef<T: ?Sized>
-    where T: marker::Reflect + 'static
+    where T: 'static
 {
     untyped: UntypedVRef,
     _0: marker::PhantomData<*const T>,
@@ -108,14 +336,14 @@ struct VOffset {
 }
 
 struct VData<'a, S>
-    where S: Sized + marker::Reflect + 'static
+    where S: Sized + 'static
 {
     v_offset: VOffset,
     ptr: &'a S,
 }
 
 struct VDataMut<'a, S>
-    where S: Sized + marker::Reflect + 'static
+    where S: Sized + 'static
 {
     v_offset: VOffset,
     ptr: &'a mut S,
@@ -123,7 +351,7 @@ struct VDataMut<'a, S>
 
 impl UntypedVRef {
     pub fn new(v_table: &'static VTable) -> UntypedVRef {
-        UntypedVRef { v_table: v_table }
+        UntypedVRef { v_table }
     }
 
     pub fn v_table(&self) -> &'static VTable { self.v_table }
@@ -137,37 +365,33 @@ impl UntypedVRef {
     }
 
     pub fn up_cast<T: ?Sized, B: ?Sized>(&self) -> UntypedVRef
-        where B: marker::Reflect + 'static,
-              T: TraitExtendTrait<B> + marker::Reflect + 'static
+        where B: 'static,
+              T: TraitExtendTrait<B> + 'static
     {
         let v_table: &'static VTable = unsafe {
-            let raw: *const u8 = mem::transmute(self.v_table);
-            mem::transmute(raw as isize + <T as TraitExtendTrait<B>>::offset())
+            let raw = self.v_table as *const VTable as *const u8;
+            &*(raw.offset(<T as TraitExtendTrait<B>>::offset()) as *const VTable)
         };
 
         UntypedVRef::new(v_table)
     }
 
-    pub fn down_cast<T: ?Sized, D: ?Sized>(&self) -> Option<UntypedVRef>
-        where T: marker::Reflect + 'static,
-              D: TraitExtendTrait<T> + marker::Reflect + 'static
+    pub fn down_cast<T: ?Sized, D: ?Sized>(&self) -> Result<UntypedVRef, CastError>
+        where T: 'static,
+              D: TraitExtendTrait<T> + 'static
     {
-        if trait_id::<T>() == trait_id::<D>() { return Some(*self); }
+        if trait_id::<T>() == trait_id::<D>() { return Ok(*self); }
 
-        self.v_table().cast_to_trait::<D>().map(|vt| {
-            UntypedVRef::new(vt)
-        })
+        self.v_table().cast_to_trait::<D>().map(UntypedVRef::new).map_err(CastError::from)
     }
 
-    pub fn cast<T: ?Sized, X: ?Sized>(&self) -> Option<UntypedVRef>
-        where T: marker::Reflect + 'static,
-              X: marker::Reflect + 'static
+    pub fn cast<T: ?Sized, X: ?Sized>(&self) -> Result<UntypedVRef, CastError>
+        where T: 'static,
+              X: 'static
     {
-        if trait_id::<T>() == trait_id::<X>() { return Some(*self); }
+        if trait_id::<T>() == trait_id::<X>() { return Ok(*self); }
 
-        self.v_table().cast_to_trait::<X>().map(|vt| {
-            UntypedVRef::new(vt)
-        })
+        self.v_table().cast_to_trait::<X>().map(UntypedVRef::new).map_err(CastError::from)
     }
 
     pub fn drop(&self, it: *mut ()) {
@@ -186,10 +410,10 @@ impl fmt::Debug for UntypedVRef {
 }
 
 impl<T: ?Sized> VRef<T>
-    where T: marker::Reflect + 'static
+    where T: 'static
 {
     pub fn new<S>() -> VRef<T>
-        where S: ExtendTrait<T> + marker::Reflect + 'static
+        where S: ExtendTrait<T> + 'static
     {
         VRef {
             untyped: UntypedVRef::new(v_table::<T, S>()),
@@ -197,6 +421,16 @@ impl<T: ?Sized> VRef<T>
         }
     }
 
+    //  The VRef of the struct behind `it`, or an error if it is not registered.
+    pub fn try_of(it: &T) -> Result<VRef<T>, RegistryError>
+        where T: PolyObject
+    {
+        Ok(VRef {
+            untyped: UntypedVRef::new(try_v_table_of(it)?),
+            _0: marker::PhantomData
+        })
+    }
+
     pub fn v_table(&self) -> &'static VTable {
         self.untyped.v_table()
     }
@@ -210,22 +444,22 @@ impl<T: ?Sized> VRef<T>
     }
 
     pub fn up_cast<B: ?Sized>(&self) -> VRef<B>
-        where B: marker::Reflect + 'static,
+        where B: 'static,
               T: TraitExtendTrait<B>
     {
         VRef { untyped: self.untyped.up_cast::<T, B>(), _0: marker::PhantomData }
     }
 
-    pub fn down_cast<D: ?Sized>(&self) -> Option<VRef<D>>
-        where D: TraitExtendTrait<T> + marker::Reflect + 'static
+    pub fn down_cast<D: ?Sized>(&self) -> Result<VRef<D>, CastError>
+        where D: TraitExtendTrait<T> + 'static
     {
         self.untyped.down_cast::<T, D>().map(|u| {
             VRef { untyped: u, _0: marker::PhantomData }
         })
     }
 
-    pub fn cast<X: ?Sized>(&self) -> Option<VRef<X>>
-        where X: marker::Reflect + 'static
+    pub fn cast<X: ?Sized>(&self) -> Result<VRef<X>, CastError>
+        where X: 'static
     {
         self.untyped.cast::<T, X>().map(|u| {
             VRef { untyped: u, _0: marker::PhantomData }
@@ -233,9 +467,9 @@ impl<T: ?Sized> VRef<T>
     }
 
     pub fn is<S>(&self) -> bool
-        where S: marker::Reflect + 'static
+        where S: 'static
     {
-        self.struct_info().offsets(struct_id::<S>()).len() > 0
+        !self.struct_info().offsets(struct_id::<S>()).is_empty()
     }
 
     pub fn drop(&self, it: *mut ()) {
@@ -244,20 +478,20 @@ impl<T: ?Sized> VRef<T>
 } // impl VRef
 
 impl<T: ?Sized> clone::Clone for VRef<T>
-    where T: marker::Reflect + 'static
+    where T: 'static
 {
     fn clone(&self) -> Self {
-        VRef { untyped: self.untyped.clone(), _0: marker::PhantomData }
+        *self
     }
 }
 
 impl<T: ?Sized> marker::Copy for VRef <T>
-    where T: marker::Reflect + 'static
+    where T: 'static
 {
 }
 
 impl<T: ?Sized> fmt::Debug for VRef<T>
-    where T: marker::Reflect + 'static
+    where T: 'static
 {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -293,6 +527,8 @@ impl VOffset {
 
     pub fn offset(&self) -> isize { (self.bundled_offset & VOffset::MULT_MASK) as isize }
 
+    pub fn offset_into_struct(&self) -> isize { self.offset() - self.base_offset() }
+
     fn log2_align(&self) -> isize { (self.bundled_offset >> VOffset::ALIGN_SHIFT) as isize }
 
     fn multiplier(&self) -> isize {
@@ -301,37 +537,50 @@ impl VOffset {
 }
 
 impl<'a, S> VData<'a, S>
-    where S: marker::Reflect + 'static
+    where S: 'static
 {
     fn new(offset: VOffset, ptr: &'a S) -> VData<'a, S> {
-        VData { v_offset: offset, ptr: ptr }
+        VData { v_offset: offset, ptr }
     }
 
-    fn base_offset(&self) -> isize { self.v_offset.base_offset() }
-
     fn offset(&self) -> isize { self.v_offset.offset() }
 
     fn as_struct(&self) -> &S { self.ptr }
+
+    //  Pointer to the most derived struct.
+    fn base_ptr(&self) -> *const () {
+        let ptr = self.ptr as *const S as *const u8;
+        ptr.wrapping_offset(-self.v_offset.offset_into_struct()) as *const ()
+    }
 } // impl VData
 
 impl<'a, S> VDataMut<'a, S>
-    where S: marker::Reflect + 'static
+    where S: 'static
 {
     fn new(offset: VOffset, ptr: &'a mut S) -> VDataMut<'a, S> {
-        VDataMut { v_offset: offset, ptr: ptr }
+        VDataMut { v_offset: offset, ptr }
     }
 
-    fn base_offset(&self) -> isize { self.v_offset.base_offset() }
-
     fn offset(&self) -> isize { self.v_offset.offset() }
 
     fn as_struct(&self) -> &S { self.ptr }
 
     fn as_struct_mut(&mut self) -> &mut S { self.ptr }
+
+    //  Pointer to the most derived struct.
+    fn base_ptr(&self) -> *const () {
+        let ptr = &*self.ptr as *const S as *const u8;
+        ptr.wrapping_offset(-self.v_offset.offset_into_struct()) as *const ()
+    }
+
+    fn base_ptr_mut(&mut self) -> *mut () {
+        let ptr = self.ptr as *mut S as *mut u8;
+        ptr.wrapping_offset(-self.v_offset.offset_into_struct()) as *mut ()
+    }
 }
 
 impl<'a, S> clone::Clone for VData<'a, S>
-    where S: marker::Reflect + 'static,
+    where S: 'static,
 {
     fn clone(&self) -> Self {
         VData { v_offset: self.v_offset, ptr: self.ptr }
@@ -339,7 +588,7 @@ impl<'a, S> clone::Clone for VData<'a, S>
 }
 
 impl<'a, S> fmt::Debug for VData<'a, S>
-    where S: fmt::Debug + marker::Reflect + 'static,
+    where S: fmt::Debug + 'static,
 {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -352,7 +601,7 @@ impl<'a, S> fmt::Debug for VData<'a, S>
 }
 
 impl<'a, S> fmt::Debug for VDataMut<'a, S>
-    where S: fmt::Debug + marker::Reflect + 'static,
+    where S: fmt::Debug + 'static,
 {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -365,47 +614,53 @@ impl<'a, S> fmt::Debug for VDataMut<'a, S>
 }
 
 trait VDataImpl<Pointer> {
-    type Inner: Sized + marker::Reflect + 'static;
+    type Inner: Sized + 'static;
 
     unsafe fn make(v_offset: VOffset, ptr: Pointer) -> Self;
 
+    fn v_offset(&self) -> VOffset;
+
     unsafe fn add_offset<Target>(self, off: isize) -> Target
         where Target: VDataImpl<Pointer> + Sized;
 }
 
 impl<'a, S> VDataImpl<*const u8> for VData<'a, S>
-    where S: Sized + marker::Reflect + 'static
+    where S: Sized + 'static
 {
     type Inner = S;
 
     unsafe fn make(v_offset: VOffset, ptr: *const u8) -> Self {
-        VData::new(v_offset, mem::transmute(ptr))
+        VData::new(v_offset, &*(ptr as *const S))
     }
 
+    fn v_offset(&self) -> VOffset { self.v_offset }
+
     unsafe fn add_offset<Target>(self, o: isize) -> Target
         where Target: VDataImpl<*const u8> + Sized
     {
         let new_v_offset = self.v_offset.new_offset(self.offset() + o);
-        let ptr: *const u8 = mem::transmute(self.ptr);
+        let ptr = self.ptr as *const S as *const u8;
 
         Target::make(new_v_offset, ptr.offset(o))
     }
 }
 
 impl<'a, S> VDataImpl<*mut u8> for VDataMut<'a, S>
-    where S: Sized + marker::Reflect + 'static
+    where S: Sized + 'static
 {
     type Inner = S;
 
     unsafe fn make(v_offset: VOffset, ptr: *mut u8) -> Self {
-        VDataMut::new(v_offset, mem::transmute(ptr))
+        VDataMut::new(v_offset, &mut *(ptr as *mut S))
     }
 
+    fn v_offset(&self) -> VOffset { self.v_offset }
+
     unsafe fn add_offset<Target>(self, o: isize) -> Target
         where Target: VDataImpl<*mut u8> + Sized
     {
         let new_v_offset = self.v_offset.new_offset(self.offset() + o);
-        let ptr: *mut u8 = mem::transmute(self.ptr);
+        let ptr = self.ptr as *mut S as *mut u8;
 
         Target::make(new_v_offset, ptr.offset(o))
     }
@@ -416,91 +671,130 @@ trait VDataCast<Target, Pointer>
           Target: VDataImpl<Pointer> + Sized
 {
     fn up_cast(self) -> Target
-        where Self::Inner: ExtendStruct<Target::Inner>,
+        where Self::Inner: UniqueExtendStruct<Target::Inner>,
     {
-        if struct_id::<Self::Inner>() == struct_id::<Target::Inner>() {
-            return unsafe { self.add_offset(0) };
-        }
+        let offsets = <Self::Inner as ExtendStruct<Target::Inner>>::offsets();
 
+        unsafe { self.add_offset(offsets[0]) }
+    }
+
+    fn try_up_cast(self) -> Result<Target, (Self, CastError)>
+        where Self::Inner: ExtendStruct<Target::Inner>,
+    {
         let offsets = <Self::Inner as ExtendStruct<Target::Inner>>::offsets();
-        assert!(offsets.len() == 1, "Multiple offsets support not implemented yet");
 
-        unsafe { self.add_offset(*offsets.get_unchecked(0)) }
+        match offsets.len() {
+        1 => unsafe { Ok(self.add_offset(offsets[0])) },
+        0 => Err((self, CastError::Mismatch)),
+        n => Err((self, CastError::Ambiguous { candidates: n })),
+        }
+    }
+
+    fn up_cast_path<Path>(self) -> Target
+        where Path: CastPath<Self::Inner, Target = Target::Inner>,
+    {
+        unsafe { self.add_offset(Path::offset()) }
     }
 
-    fn down_cast<T: ?Sized>(self, v_ref: VRef<T>) -> Result<Target, Self>
-        where T: marker::Reflect + 'static,
+    //  Self is a specific sub-object of the most derived struct, hence down-casting is never
+    //  ambiguous: the Target sub-object, if any, is the one containing Self.
+    //
+    //  The exception is `()`, the common ancestor of all structs, which stands for the whole
+    //  object rather than a specific sub-object: down-casting from it is a cross-cast.
+    fn down_cast<T: ?Sized>(self, v_ref: VRef<T>) -> Result<Target, (Self, CastError)>
+        where T: 'static,
               Target::Inner: ExtendStruct<Self::Inner>,
     {
         if struct_id::<Self::Inner>() == struct_id::<Target::Inner>() {
             return unsafe { Ok(self.add_offset(0)) };
         }
 
-        if !v_ref.is::<Target::Inner>() { return Err(self); }
+        if is_root::<Self::Inner>() {
+            return self.cast(v_ref);
+        }
 
-        let offsets = <Target::Inner as ExtendStruct<Self::Inner>>::offsets();
-        assert!(offsets.len() == 1, "Support for diamond inheritance is not yet implemented!");
+        //  The offsets of Target::Inner are relative to the most derived struct, those of
+        //  Self::Inner within Target::Inner are relative to Target::Inner.
+        let current = self.v_offset().offset_into_struct();
+        let targets = v_ref.struct_info().offsets(struct_id::<Target::Inner>());
+        let inners = <Target::Inner as ExtendStruct<Self::Inner>>::offsets();
 
-        unsafe { Ok(self.add_offset(*offsets.get_unchecked(0))) }
+        match targets.iter().find(|t| inners.contains(&(current - **t))) {
+        Some(t) => unsafe { Ok(self.add_offset(*t - current)) },
+        None    => Err((self, CastError::Mismatch)),
+        }
     }
 
-    fn cast<T: ?Sized>(self, v_ref: VRef<T>) -> Result<Target, Self>
-        where T: marker::Reflect + 'static,
+    fn cast<T: ?Sized>(self, v_ref: VRef<T>) -> Result<Target, (Self, CastError)>
+        where T: 'static,
     {
-        if struct_id::<Self::Inner>() == struct_id::<Target::Inner>() {
+        //  Any sub-object may stand for the whole object.
+        if struct_id::<Self::Inner>() == struct_id::<Target::Inner>() || is_root::<Target::Inner>() {
             return unsafe { Ok(self.add_offset(0)) };
         }
 
         let offsets = v_ref.struct_info().offsets(struct_id::<Target::Inner>());
-        assert!(offsets.len() <= 1, "Support for diamond inheritance is not yet implemented!");
 
-        match offsets.first() {
-        Some(o) => unsafe { Ok(self.add_offset(*o)) },
-        None    => Err(self),
+        //  The offsets are relative to the most derived struct, not to Self::Inner.
+        let current = self.v_offset().offset_into_struct();
+
+        match *offsets {
+        [o] => unsafe { Ok(self.add_offset(o - current)) },
+        []  => Err((self, CastError::Mismatch)),
+        _   => Err((self, CastError::Ambiguous { candidates: offsets.len() })),
         }
     }
 }
 
+//  Whether S is `()`, the common ancestor of all structs, see Dyn.
+fn is_root<S>() -> bool
+    where S: 'static,
+{
+    struct_id::<S>() == struct_id::<()>()
+}
+
 impl<'a, S, Y> VDataCast<VData<'a, Y>, *const u8> for VData<'a, S>
-    where S: marker::Reflect + 'static,
-          Y: marker::Reflect + 'static,
+    where S: 'static,
+          Y: 'static,
 {}
 
 impl<'a, S, Y> VDataCast<VDataMut<'a, Y>, *mut u8> for VDataMut<'a, S>
-    where S: marker::Reflect + 'static,
-          Y: marker::Reflect + 'static,
+    where S: 'static,
+          Y: 'static,
 {}
 
 
 //
-//  Class, DynClass (& Dyn), DynRef, DynRefMut
+//  Class, DynClass (& Dyn), BoxDyn, DynRef, DynRefMut
 //
 #[repr(C)]
 #[derive(Debug)]
 pub struct Class<T: ?Sized, S>
-    where T: marker::Reflect + 'static,
-          S: ExtendTrait<T> + marker::Reflect + 'static,
+    where T: 'static,
+          S: ExtendTrait<T> + 'static,
 {
-    dyn: DynClass<T, S>,
-    data: S,
+    dyn_class: DynClass<T, S>,
+    data: mem::ManuallyDrop<S>,     // dropped by dyn_class
 }
 
 #[repr(C)]
 pub struct DynClass<T: ?Sized, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
 {
     v_ref: VRef<T>,
     v_offset: VOffset,
     _0: marker::PhantomData<S>,
 }
 
+//  A DynClass whose static struct is `()`, the common ancestor of all structs: it stands for
+//  the whole object, whichever its most derived struct.
 pub type Dyn<T> = DynClass<T, ()>;
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct DynRef<'a, T: ?Sized, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
 {
     v_ref: VRef<T>,
     v_data: VData<'a, S>,
@@ -508,8 +802,8 @@ pub struct DynRef<'a, T: ?Sized, S>
 
 #[derive(Debug)]
 pub struct DynRefMut<'a, T: ?Sized, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
 {
     v_ref: VRef<T>,
     v_data: VDataMut<'a, S>,
@@ -519,13 +813,13 @@ pub struct DynRefMut<'a, T: ?Sized, S>
 //  Class
 //
 impl<T: ?Sized, S> Class<T, S>
-    where T: marker::Reflect + 'static,
-          S: ExtendTrait<T> + marker::Reflect + 'static,
+    where T: 'static,
+          S: ExtendTrait<T> + 'static,
 {
     pub fn new(data: S) -> Class<T, S> {
-        assert!(offset_of!(Self, dyn) == 0, "Essential for &Class -> &DynClass conversion!");
+        assert!(mem::offset_of!(Self, dyn_class) == 0, "Essential for &Class -> &DynClass conversion!");
 
-        let offset = offset_of!(Self, data);
+        let offset = mem::offset_of!(Self, data) as isize;
 
         fn compact(n: isize) -> (u8, u8) {
             assert!(n > 0);
@@ -545,17 +839,20 @@ impl<T: ?Sized, S> Class<T, S>
         let (log2_align, mult) = compact(offset);
         let v_offset = VOffset::new(log2_align, mult, offset);
 
-        Class { dyn: unsafe { DynClass::new(VRef::new::<S>(), v_offset) }, data: data }
+        Class {
+            dyn_class: unsafe { DynClass::new(VRef::new::<S>(), v_offset) },
+            data: mem::ManuallyDrop::new(data),
+        }
     }
 } // impl Class
 
 impl<T: ?Sized, S> clone::Clone for Class<T, S>
-    where T: marker::Reflect + 'static,
-          S: ExtendTrait<T> + clone::Clone + marker::Reflect + 'static,
+    where T: 'static,
+          S: ExtendTrait<T> + clone::Clone + 'static,
 {
     fn clone(&self) -> Self {
-        let new_dyn = unsafe { DynClass::new(self.dyn.v_ref, self.dyn.v_offset) };
-        Class { dyn: new_dyn, data: self.data.clone() }
+        let new_dyn = unsafe { DynClass::new(self.dyn_class.v_ref, self.dyn_class.v_offset) };
+        Class { dyn_class: new_dyn, data: self.data.clone() }
     }
 }
 
@@ -563,79 +860,72 @@ impl<T: ?Sized, S> clone::Clone for Class<T, S>
 //  DynClass
 //
 impl<T: ?Sized, S> DynClass<T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
 {
     unsafe fn new(v_ref: VRef<T>, v_offset: VOffset) -> DynClass<T, S> {
-        DynClass { v_ref: v_ref, v_offset: v_offset, _0: marker::PhantomData }
+        DynClass { v_ref, v_offset, _0: marker::PhantomData }
     }
 
+    //  The v-table is that of the most derived struct, hence it is paired with base_ptr().
     pub fn as_trait(&self) -> &T {
-        unsafe {
-            mem::transmute_copy(&raw::TraitObject {
-                data:   mem::transmute(self.as_struct()),
-                vtable: self.v_ref.v_table().table(),
-            })
-        }
+        let raw = self.v_ref.v_table().as_trait_ptr::<T>(self.base_ptr() as *mut ());
+        unsafe { &*raw }
     }
 
     pub fn as_trait_mut(&mut self) -> &mut T {
-        unsafe {
-            mem::transmute_copy(&raw::TraitObject {
-                data:   mem::transmute(self.as_struct_mut()),
-                vtable: self.v_ref.v_table().table(),
-            })
-        }
+        let raw = self.v_ref.v_table().as_trait_ptr::<T>(self.base_ptr_mut());
+        unsafe { &mut *raw }
     }
 
     pub fn as_struct(&self) -> &S {
-        unsafe { mem::transmute(self.data_ptr()) }
+        unsafe { &*(self.data_ptr() as *const S) }
     }
 
     pub fn as_struct_mut(&mut self) -> &mut S {
-        unsafe { mem::transmute(self.data_ptr_mut()) }
+        unsafe { &mut *(self.data_ptr_mut() as *mut S) }
     }
 
     //  Invariant: data_ptr() = base_ptr() + offset_into_struct()
     fn data_ptr(&self) -> *const () {
-        unsafe {
-            let offset = self.v_offset.offset();
-            let base: *const u8 = mem::transmute(self);
-            mem::transmute(base.offset(offset))
-        }
+        let base = self as *const Self as *const u8;
+        unsafe { base.offset(self.v_offset.offset()) as *const () }
     }
 
     fn data_ptr_mut(&mut self) -> *mut () {
-        unsafe {
-            let offset = self.v_offset.offset();
-            let base: *mut u8 = mem::transmute(self);
-            mem::transmute(base.offset(offset))
-        }
+        let offset = self.v_offset.offset();
+        let base = self as *mut Self as *mut u8;
+        unsafe { base.offset(offset) as *mut () }
     }
 
     fn base_ptr(&self) -> *const () {
-        unsafe {
-            let offset = self.v_offset.base_offset();
-            let base: *const u8 = mem::transmute(self);
-            mem::transmute(base.offset(offset))
-        }
+        let base = self as *const Self as *const u8;
+        unsafe { base.offset(self.v_offset.base_offset()) as *const () }
     }
 
     fn base_ptr_mut(&mut self) -> *mut () {
-        unsafe {
-            let offset = self.v_offset.base_offset();
-            let base: *mut u8 = mem::transmute(self);
-            mem::transmute(base.offset(offset))
-        }
+        let offset = self.v_offset.base_offset();
+        let base = self as *mut Self as *mut u8;
+        unsafe { base.offset(offset) as *mut () }
     }
 
     fn offset_into_struct(&self) -> isize {
-        self.v_offset.offset() - self.v_offset.base_offset()
+        self.v_offset.offset_into_struct()
     }
 
-    fn up_cast_struct<P>(&self) -> VOffset
+    //  Iterates over each of the P sub-objects of S, in increasing order of offset.
+    pub fn sub_objects<P>(&self) -> SubObjects<'_, T, P>
         where S: ExtendStruct<P>,
-              P: marker::Reflect + 'static,
+              P: 'static,
+    {
+        let v_offset = VOffset::new(0, 0, self.offset_into_struct());
+
+        SubObjects::new(self.v_ref, VData::new(v_offset, self.as_struct()))
+    }
+
+    fn up_cast_struct<P>(&self) -> VOffset
+        where S: UniqueExtendStruct<P>,
+              P: 'static,
     {
         let current = VData::new(self.v_offset, self.as_struct());
         let target: VData<P> = current.up_cast();
@@ -643,68 +933,59 @@ impl<T: ?Sized, S> DynClass<T, S>
         target.v_offset
     }
 
-    fn down_cast_struct<C>(&self) -> Option<VOffset>
-        where C: ExtendStruct<S> + marker::Reflect + 'static,
+    fn down_cast_struct<C>(&self) -> Result<VOffset, CastError>
+        where C: ExtendStruct<S> + 'static,
+    {
+        let current = VData::new(self.v_offset, self.as_struct());
+
+        current.down_cast(self.v_ref).map(|vd: VData<C>| vd.v_offset).map_err(|(_, e)| e)
+    }
+
+    fn cast_struct<Y>(&self) -> Result<VOffset, CastError>
+        where Y: 'static,
     {
         let current = VData::new(self.v_offset, self.as_struct());
 
-        current.down_cast(self.v_ref).ok().map(|vd: VData<C>| vd.v_offset)
+        current.cast(self.v_ref).map(|vd: VData<Y>| vd.v_offset).map_err(|(_, e)| e)
     }
 
-    fn cast_struct<Y>(&self) -> Option<VOffset>
-        where Y: marker::Reflect + 'static,
+    fn up_cast_struct_path<Path>(&self) -> VOffset
+        where Path: CastPath<S>,
     {
         let current = VData::new(self.v_offset, self.as_struct());
+        let target: VData<Path::Target> = current.up_cast_path::<Path>();
 
-        current.cast(self.v_ref).ok().map(|vd: VData<Y>| vd.v_offset)
+        target.v_offset
     }
 } // impl DynClass
 
 impl<T: ?Sized, S> DynClass<T, S>
-    where T: RawClone + marker::Reflect + 'static,
-          S: marker::Reflect + 'static
+    where T: RawClone + 'static,
+          S: 'static
 {
-    pub fn clone_to_box(&self) -> Box<DynClass<T, S>> {
-        //  Okay... so we need to estimate how much memory we will need for this,
-        //  and what the alignment of this memory should be.
-        let (size, align) = {
-            use core::cmp;
-
-            assert!(self.v_offset.base_offset() >= 0);
-
-            let struct_info = self.v_ref.struct_info();
-
-            let size = self.v_offset.base_offset() as usize + struct_info.size();
-            let align = cmp::max(
-                mem::align_of::<DynClass<T, S>>(),
-                (1_usize << struct_info.log2_align())
-            );
-            (size, align)
-        };
-
-        let original: &DynClass<T, S> = &*self;
+    pub fn clone_to_box(&self) -> BoxDyn<T, S> {
+        //  The most derived struct is cloned, not just S, hence the allocation is laid out
+        //  as the original one.
+        let layout = class_layout(self.v_ref.untyped, self.v_offset);
 
         unsafe {
-            use alloc::heap;
-            use core::ptr;
-
-            let raw = heap::allocate(size, align);
+            let raw = alloc::alloc(layout);
+            if raw.is_null() { alloc::handle_alloc_error(layout); }
 
-            let head    : *const u8 = mem::transmute(original);
-            let head_raw: *mut u8   = raw;
-            ptr::copy_nonoverlapping(head, head_raw, mem::size_of::<DynClass<T, S>>());
+            let head = self as *const DynClass<T, S> as *const u8;
+            ptr::copy_nonoverlapping(head, raw, mem::size_of::<DynClass<T, S>>());
 
-            let tail_raw: *mut u8   = head_raw.offset(original.v_offset.offset());
-            original.raw_clone(tail_raw);
+            let tail_raw = raw.offset(self.v_offset.base_offset());
+            self.as_trait().raw_clone(tail_raw);
 
-            mem::transmute(raw)
+            BoxDyn::from_raw(raw as *mut DynClass<T, S>)
         }
     }
 }
 
 impl<T: ?Sized, S> fmt::Debug for DynClass<T, S>
-    where T: marker::Reflect + 'static,
-          S: fmt::Debug + marker::Reflect + 'static
+    where T: 'static,
+          S: fmt::Debug + 'static
 {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -718,8 +999,8 @@ impl<T: ?Sized, S> fmt::Debug for DynClass<T, S>
 } // impl Debug for DynClass
 
 impl<T: ?Sized, S> Drop for DynClass<T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
 {
     fn drop(&mut self) {
         let v_ref = self.v_ref;
@@ -728,8 +1009,8 @@ impl<T: ?Sized, S> Drop for DynClass<T, S>
 }
 
 impl<T: ?Sized, S> ops::Deref for DynClass<T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static
+    where T: 'static,
+          S: 'static
 {
     type Target = T;
 
@@ -737,59 +1018,191 @@ impl<T: ?Sized, S> ops::Deref for DynClass<T, S>
 } // impl Deref
 
 impl<T: ?Sized, S> ops::DerefMut for DynClass<T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static
+    where T: 'static,
+          S: 'static
 {
     fn deref_mut(&mut self) -> &mut T { self.as_trait_mut() }
 } // impl DerefMut
 
-impl<T: ?Sized, S> clone::Clone for Box<DynClass<T, S>>
-    where T: RawClone + marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
+//  Layout of an allocation holding the header and the most derived struct, as laid out by
+//  Class; whichever the view of the header, it is recomputed from the StructInfo.
+fn class_layout(v_ref: UntypedVRef, v_offset: VOffset) -> alloc::Layout {
+    let struct_info = v_ref.struct_info();
+
+    alloc::Layout::from_size_align(
+        v_offset.base_offset() as usize + struct_info.size(),
+        cmp::max(mem::align_of::<Header>(), 1_usize << struct_info.log2_align()),
+    ).expect("Invalid layout").pad_to_align()
+}
+
+//
+//  BoxDyn
+//
+//  An owning thin pointer to a DynClass, at the start of an allocation laid out by Class.
+//
+//  Once cast, the DynClass no longer describes the allocated type, hence a plain Box could
+//  not free it with the right layout; BoxDyn recomputes it, see class_layout.
+//
+#[repr(transparent)]
+pub struct BoxDyn<T: ?Sized, S>
+    where T: 'static,
+          S: 'static,
+{
+    ptr: ptr::NonNull<DynClass<T, S>>,
+    _0: marker::PhantomData<DynClass<T, S>>,
+}
+
+impl<T: ?Sized, S> BoxDyn<T, S>
+    where T: 'static,
+          S: ExtendTrait<T> + 'static,
+{
+    pub fn new(class: Class<T, S>) -> BoxDyn<T, S> {
+        let layout = alloc::Layout::new::<Class<T, S>>();
+
+        debug_assert_eq!(layout, class_layout(class.dyn_class.v_ref.untyped, class.dyn_class.v_offset));
+
+        unsafe {
+            let raw = alloc::alloc(layout) as *mut Class<T, S>;
+            if raw.is_null() { alloc::handle_alloc_error(layout); }
+
+            ptr::write(raw, class);
+
+            BoxDyn::from_raw(raw as *mut DynClass<T, S>)
+        }
+    }
+} // impl BoxDyn
+
+impl<T: ?Sized, S> BoxDyn<T, S>
+    where T: 'static,
+          S: 'static,
+{
+    //  Safety: `raw` is the start of an allocation, from the global allocator, laid out as
+    //  per class_layout.
+    unsafe fn from_raw(raw: *mut DynClass<T, S>) -> BoxDyn<T, S> {
+        BoxDyn { ptr: ptr::NonNull::new_unchecked(raw), _0: marker::PhantomData }
+    }
+
+    //  Up-casts to the P sub-object of M, picking one P when several exist in S.
+    pub fn up_cast_via<M, P>(self) -> BoxDyn<T, P>
+        where S: UniqueExtendStruct<M>,
+              M: UniqueExtendStruct<P> + 'static,
+              P: 'static,
+    {
+        self.up_cast_path::<(M, P)>()
+    }
+
+    //  Up-casts to the sub-object designated by Path, see CastPath.
+    pub fn up_cast_path<Path>(self) -> BoxDyn<T, Path::Target>
+        where Path: CastPath<S>,
+    {
+        let new_v_offset = self.up_cast_struct_path::<Path>();
+        let v_ref = self.v_ref;
+
+        self.with_view(v_ref, new_v_offset)
+    }
+
+    //  Re-targets the view, in place.
+    fn with_view<X: ?Sized, Y>(self, v_ref: VRef<X>, v_offset: VOffset) -> BoxDyn<X, Y>
+        where X: 'static,
+              Y: 'static,
+    {
+        let this = mem::ManuallyDrop::new(self);
+        let mut ptr = this.ptr.cast::<DynClass<X, Y>>();
+
+        unsafe {
+            ptr.as_mut().v_ref = v_ref;
+            ptr.as_mut().v_offset = v_offset;
+        }
+
+        BoxDyn { ptr, _0: marker::PhantomData }
+    }
+} // impl BoxDyn
+
+impl<T: ?Sized, S> Drop for BoxDyn<T, S>
+    where T: 'static,
+          S: 'static,
+{
+    fn drop(&mut self) {
+        let layout = class_layout(self.v_ref.untyped, self.v_offset);
+
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr());
+            alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout);
+        }
+    }
+}
+
+impl<T: ?Sized, S> ops::Deref for BoxDyn<T, S>
+    where T: 'static,
+          S: 'static,
+{
+    type Target = DynClass<T, S>;
+
+    fn deref(&self) -> &DynClass<T, S> { unsafe { self.ptr.as_ref() } }
+} // impl Deref
+
+impl<T: ?Sized, S> ops::DerefMut for BoxDyn<T, S>
+    where T: 'static,
+          S: 'static,
+{
+    fn deref_mut(&mut self) -> &mut DynClass<T, S> { unsafe { self.ptr.as_mut() } }
+} // impl DerefMut
+
+impl<T: ?Sized, S> clone::Clone for BoxDyn<T, S>
+    where T: RawClone + 'static,
+          S: 'static,
 {
     fn clone(&self) -> Self {
         self.clone_to_box()
     }
 } // impl Clone
 
-impl<T: ?Sized, S> convert::From<Box<Class<T, S>>> for Box<DynClass<T, S>>
-    where T: marker::Reflect + 'static,
-          S: ExtendTrait<T> + marker::Reflect + 'static,
+impl<T: ?Sized, S> fmt::Debug for BoxDyn<T, S>
+    where T: 'static,
+          S: fmt::Debug + 'static,
 {
-    fn from(t: Box<Class<T, S>>) -> Box<DynClass<T, S>> {
-        unsafe { mem::transmute(t) }
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, formatter)
     }
+} // impl Debug
+
+impl<T: ?Sized, S> convert::From<Class<T, S>> for BoxDyn<T, S>
+    where T: 'static,
+          S: ExtendTrait<T> + 'static,
+{
+    fn from(class: Class<T, S>) -> BoxDyn<T, S> { BoxDyn::new(class) }
+}
+
+impl<T: ?Sized, S> convert::From<Box<Class<T, S>>> for BoxDyn<T, S>
+    where T: 'static,
+          S: ExtendTrait<T> + 'static,
+{
+    fn from(class: Box<Class<T, S>>) -> BoxDyn<T, S> { BoxDyn::new(*class) }
 }
 
 //
 //  Casting
 //
-impl<T: ?Sized, S, B: ?Sized, P> UpCast<Box<DynClass<B, P>>> for Box<DynClass<T, S>>
-    where T: TraitExtendTrait<B> + marker::Reflect + 'static,
-          S: ExtendStruct<P> + marker::Reflect + 'static,
-          B: marker::Reflect + 'static,
-          P: marker::Reflect + 'static,
+impl<T: ?Sized, S, B: ?Sized, P> UpCast<BoxDyn<B, P>> for BoxDyn<T, S>
+    where T: TraitExtendTrait<B> + 'static,
+          S: UniqueExtendStruct<P> + 'static,
+          B: 'static,
+          P: 'static,
 {
-    fn up_cast(self) -> Box<DynClass<B, P>> {
-        //  Compute new v_ref and offset
+    fn up_cast(self) -> BoxDyn<B, P> {
         let new_v_ref = self.v_ref.up_cast::<B>();
 
         let new_v_offset = self.up_cast_struct::<P>();
 
-        //  Commit result
-        let mut s: Box<DynClass<B, P>> = unsafe { mem::transmute(self) };
-        s.v_ref = new_v_ref;
-        s.v_offset = new_v_offset;
-
-        s
+        self.with_view(new_v_ref, new_v_offset)
     }
 }
 
 impl<T: ?Sized, S, B: ?Sized, P> UpCastRef<DynClass<B, P>> for DynClass<T, S>
-    where T: FirstExtendTrait<B> + marker::Reflect + 'static,
-          S: FirstExtendStruct<P> + marker::Reflect + 'static,
-          B: marker::Reflect + 'static,
-          P: marker::Reflect + 'static,
+    where T: FirstExtendTrait<B> + 'static,
+          S: FirstExtendStruct<P> + 'static,
+          B: 'static,
+          P: 'static,
 {
     fn up_cast_ref(&self) -> &DynClass<B, P> {
         unsafe { mem::transmute(self) }
@@ -800,28 +1213,28 @@ impl<T: ?Sized, S, B: ?Sized, P> UpCastRef<DynClass<B, P>> for DynClass<T, S>
     }
 }
 
-impl<T: ?Sized, S, B: ?Sized, P> UpCastRef<Box<DynClass<B, P>>> for Box<DynClass<T, S>>
-    where T: FirstExtendTrait<B> + marker::Reflect + 'static,
-          S: FirstExtendStruct<P> + marker::Reflect + 'static,
-          B: marker::Reflect + 'static,
-          P: marker::Reflect + 'static,
+impl<T: ?Sized, S, B: ?Sized, P> UpCastRef<BoxDyn<B, P>> for BoxDyn<T, S>
+    where T: FirstExtendTrait<B> + 'static,
+          S: FirstExtendStruct<P> + 'static,
+          B: 'static,
+          P: 'static,
 {
-    fn up_cast_ref(&self) -> &Box<DynClass<B, P>> {
+    fn up_cast_ref(&self) -> &BoxDyn<B, P> {
         unsafe { mem::transmute(self) }
     }
 
-    fn up_cast_ref_mut(&mut self) -> &mut Box<DynClass<B, P>> {
+    fn up_cast_ref_mut(&mut self) -> &mut BoxDyn<B, P> {
         unsafe { mem::transmute(self) }
     }
 }
 
-impl<T: ?Sized, S, D: ?Sized, C> DownCast<Box<DynClass<D, C>>> for Box<DynClass<T, S>>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
-          D: TraitExtendTrait<T> + marker::Reflect + 'static,
-          C: FirstExtendStruct<S> + marker::Reflect + 'static,
+impl<T: ?Sized, S, D: ?Sized, C> DownCast<BoxDyn<D, C>> for BoxDyn<T, S>
+    where T: 'static,
+          S: 'static,
+          D: TraitExtendTrait<T> + 'static,
+          C: FirstExtendStruct<S> + 'static,
 {
-    fn down_cast(self) -> Result<Box<DynClass<D, C>>, Box<DynClass<T, S>>> {
+    fn down_cast(self) -> Result<BoxDyn<D, C>, (BoxDyn<T, S>, CastError)> {
         //  Compute new v_ref and offset, while checking whether they do apply.
         let new_v_ref = self.v_ref.down_cast::<D>();
 
@@ -829,56 +1242,49 @@ impl<T: ?Sized, S, D: ?Sized, C> DownCast<Box<DynClass<D, C>>> for Box<DynClass<
 
         //  Check whether the conversion makes sense,
         //  return the result appropriately.
-        if let (Some(r), Some(o)) = (new_v_ref, new_v_offset) {
-            let mut s: Box<DynClass<D, C>> = unsafe { mem::transmute(self) };
-            s.v_ref = r;
-            s.v_offset = o;
-            Ok(s)
-        } else {
-            Err(self)
+        match (new_v_ref, new_v_offset) {
+        (Ok(r), Ok(o)) => Ok(self.with_view(r, o)),
+        (Err(e), _) | (_, Err(e)) => Err((self, e)),
         }
     }
 
-    unsafe fn unchecked_down_cast(self) -> Box<DynClass<D, C>> {
+    unsafe fn unchecked_down_cast(self) -> BoxDyn<D, C> {
         //  Compute new v_ref and offset, while checking whether they do apply.
         let new_v_ref = self.v_ref.down_cast::<D>().unwrap();
 
         let new_v_offset = self.down_cast_struct::<C>().unwrap();
 
-        //  Commit result
-        let mut s: Box<DynClass<D, C>> = mem::transmute(self);
-        s.v_ref = new_v_ref;
-        s.v_offset = new_v_offset;
-
-        s
+        self.with_view(new_v_ref, new_v_offset)
     }
 }
 
 impl<T: ?Sized, S, D: ?Sized, C> DownCastRef<DynClass<D, C>> for DynClass<T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
-          D: FirstExtendTrait<T> + TraitExtendTrait<T> + marker::Reflect + 'static,
-          C: FirstExtendStruct<S> + marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
+          D: FirstExtendTrait<T> + TraitExtendTrait<T> + 'static,
+          C: FirstExtendStruct<S> + 'static,
 {
     fn down_cast_ref(&self) -> Option<&DynClass<D, C>> {
-        let is_trait_ok = self.v_ref.down_cast::<D>().is_some();
+        let is_trait_ok = self.v_ref.down_cast::<D>().is_ok();
 
-        let is_struct_ok = self.down_cast_struct::<C>().is_some();
+        //  The header is reinterpreted in place, hence C must start where S does; this is
+        //  not a given when down-casting from `()`.
+        let is_struct_ok = self.down_cast_struct::<C>().is_ok_and(|o| o.offset() == self.v_offset.offset());
 
         if is_trait_ok && is_struct_ok {
-            Some(unsafe { mem::transmute(self) })
+            Some(unsafe { &*(self as *const Self as *const DynClass<D, C>) })
         } else {
             None
         }
     }
 
     fn down_cast_ref_mut(&mut self) -> Option<&mut DynClass<D, C>> {
-        let is_trait_ok = self.v_ref.down_cast::<D>().is_some();
+        let is_trait_ok = self.v_ref.down_cast::<D>().is_ok();
 
-        let is_struct_ok = self.down_cast_struct::<C>().is_some();
+        let is_struct_ok = self.down_cast_struct::<C>().is_ok_and(|o| o.offset() == self.v_offset.offset());
 
         if is_trait_ok && is_struct_ok {
-            Some(unsafe { mem::transmute(self) })
+            Some(unsafe { &mut *(self as *mut Self as *mut DynClass<D, C>) })
         } else {
             None
         }
@@ -893,38 +1299,31 @@ impl<T: ?Sized, S, D: ?Sized, C> DownCastRef<DynClass<D, C>> for DynClass<T, S>
     }
 }
 
-impl<T: ?Sized, S, X: ?Sized, Y> Cast<Box<DynClass<X, Y>>> for Box<DynClass<T, S>>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
-          X: marker::Reflect + 'static,
-          Y: marker::Reflect + 'static,
+impl<T: ?Sized, S, X: ?Sized, Y> Cast<BoxDyn<X, Y>> for BoxDyn<T, S>
+    where T: 'static,
+          S: 'static,
+          X: 'static,
+          Y: 'static,
 {
-    fn cast(self) -> Result<Box<DynClass<X, Y>>, Box<DynClass<T, S>>> {
+    fn cast(self) -> Result<BoxDyn<X, Y>, (BoxDyn<T, S>, CastError)> {
         let new_v_ref = self.v_ref.cast::<X>();
 
         let new_v_offset = self.cast_struct::<Y>();
 
         //  Check whether the conversion makes sense,
         //  return the result appropriately.
-        if let (Some(r), Some(o)) = (new_v_ref, new_v_offset) {
-            let mut s: Box<DynClass<X, Y>> = unsafe { mem::transmute(self) };
-            s.v_ref = r;
-            s.v_offset = o;
-            Ok(s)
-        } else {
-            Err(self)
+        match (new_v_ref, new_v_offset) {
+        (Ok(r), Ok(o)) => Ok(self.with_view(r, o)),
+        (Err(e), _) | (_, Err(e)) => Err((self, e)),
         }
     }
 
-    unsafe fn unchecked_cast(self) -> Box<DynClass<X, Y>> {
+    unsafe fn unchecked_cast(self) -> BoxDyn<X, Y> {
         let new_v_ref = self.v_ref.cast::<X>().unwrap();
 
         let new_v_offset = self.cast_struct::<Y>().unwrap();
 
-        let mut s: Box<DynClass<X, Y>> = mem::transmute(self);
-        s.v_ref = new_v_ref;
-        s.v_offset = new_v_offset;
-        s
+        self.with_view(new_v_ref, new_v_offset)
     }
 }
 
@@ -932,8 +1331,8 @@ impl<T: ?Sized, S, X: ?Sized, Y> Cast<Box<DynClass<X, Y>>> for Box<DynClass<T, S
 //  DynRef & DynRefMut
 //
 impl<'a, T: ?Sized, S> DynRef<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
 {
     pub fn new<CP>(c: &'a DynClass<T, S>) -> DynRef<'a, T, S>{
         let v_offset = VOffset::new(0, 0, c.offset_into_struct());
@@ -944,22 +1343,72 @@ impl<'a, T: ?Sized, S> DynRef<'a, T, S>
     }
 
     pub fn as_trait(&self) -> &T {
-        unsafe {
-            mem::transmute_copy(&raw::TraitObject {
-                data:   mem::transmute(self.as_struct()),
-                vtable: self.v_ref.v_table().table(),
-            })
-        }
+        let raw = self.v_ref.v_table().as_trait_ptr::<T>(self.v_data.base_ptr() as *mut ());
+        unsafe { &*raw }
     }
 
     pub fn as_struct(&self) -> &S {
         self.v_data.as_struct()
     }
+
+    //  Up-casts to the one and only P sub-object, or reports the ambiguity.
+    pub fn try_up_cast<P>(&self) -> Result<DynRef<'a, T, P>, CastError>
+        where S: ExtendStruct<P>,
+              P: 'static,
+    {
+        let new_v_data = self.v_data.clone().try_up_cast().map_err(|(_, e)| e)?;
+
+        Ok(DynRef { v_ref: self.v_ref, v_data: new_v_data })
+    }
+
+    //  Up-casts to each of the P sub-objects, in increasing order of offset.
+    pub fn up_cast_all<P>(&self) -> Vec<DynRef<'a, T, P>>
+        where S: ExtendStruct<P>,
+              P: 'static,
+    {
+        self.sub_objects().collect()
+    }
+
+    //  Iterates over each of the P sub-objects, in increasing order of offset.
+    pub fn sub_objects<P>(&self) -> SubObjects<'a, T, P>
+        where S: ExtendStruct<P>,
+              P: 'static,
+    {
+        SubObjects::new(self.v_ref, self.v_data.clone())
+    }
+
+    //  Up-casts to the P sub-object of M, picking one P when several exist in S.
+    pub fn up_cast_via<M, P>(self) -> DynRef<'a, T, P>
+        where S: UniqueExtendStruct<M>,
+              M: UniqueExtendStruct<P> + 'static,
+              P: 'static,
+    {
+        self.up_cast_path::<(M, P)>()
+    }
+
+    //  Up-casts to the sub-object designated by Path, see CastPath.
+    pub fn up_cast_path<Path>(self) -> DynRef<'a, T, Path::Target>
+        where Path: CastPath<S>,
+    {
+        DynRef { v_ref: self.v_ref, v_data: self.v_data.up_cast_path::<Path>() }
+    }
+
+    //  Casts to (X, Y), reporting why it failed if it does.
+    pub fn try_cast<X: ?Sized, Y>(&self) -> Result<DynRef<'a, X, Y>, CastError>
+        where X: 'static,
+              Y: 'static,
+    {
+        let new_v_ref = self.v_ref.cast::<X>()?;
+
+        let new_v_data = self.v_data.clone().cast(self.v_ref).map_err(|(_, e)| e)?;
+
+        Ok(DynRef { v_ref: new_v_ref, v_data: new_v_data })
+    }
 }
 
 impl<'a, T: ?Sized, S> DynRefMut<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
 {
     pub fn new<CP>(c: &'a mut DynClass<T, S>) -> DynRefMut<'a, T, S> {
         let v_offset = VOffset::new(0, 0, c.offset_into_struct());
@@ -970,21 +1419,13 @@ impl<'a, T: ?Sized, S> DynRefMut<'a, T, S>
     }
 
     pub fn as_trait(&self) -> &T {
-        unsafe {
-            mem::transmute_copy(&raw::TraitObject {
-                data:   mem::transmute(self.as_struct()),
-                vtable: self.v_ref.v_table().table(),
-            })
-        }
+        let raw = self.v_ref.v_table().as_trait_ptr::<T>(self.v_data.base_ptr() as *mut ());
+        unsafe { &*raw }
     }
 
     pub fn as_trait_mut(&mut self) -> &mut T {
-        unsafe {
-            mem::transmute_copy(&raw::TraitObject {
-                data:   mem::transmute(self.as_struct_mut()),
-                vtable: self.v_ref.v_table().table(),
-            })
-        }
+        let raw = self.v_ref.v_table().as_trait_ptr::<T>(self.v_data.base_ptr_mut());
+        unsafe { &mut *raw }
     }
 
     pub fn as_struct(&self) -> &S {
@@ -994,11 +1435,36 @@ impl<'a, T: ?Sized, S> DynRefMut<'a, T, S>
     pub fn as_struct_mut(&mut self) -> &mut S {
         self.v_data.as_struct_mut()
     }
+
+    //  Up-casts to the P sub-object of M, picking one P when several exist in S.
+    pub fn up_cast_via<M, P>(self) -> DynRefMut<'a, T, P>
+        where S: UniqueExtendStruct<M>,
+              M: UniqueExtendStruct<P> + 'static,
+              P: 'static,
+    {
+        self.up_cast_path::<(M, P)>()
+    }
+
+    //  Up-casts to the sub-object designated by Path, see CastPath.
+    pub fn up_cast_path<Path>(self) -> DynRefMut<'a, T, Path::Target>
+        where Path: CastPath<S>,
+    {
+        DynRefMut { v_ref: self.v_ref, v_data: self.v_data.up_cast_path::<Path>() }
+    }
+}
+
+impl<'a, T: ?Sized, S> clone::Clone for DynRef<'a, T, S>
+    where T: 'static,
+          S: 'static
+{
+    fn clone(&self) -> Self {
+        DynRef { v_ref: self.v_ref, v_data: self.v_data.clone() }
+    }
 }
 
 impl<'a, T: ?Sized, S> ops::Deref for DynRef<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static
+    where T: 'static,
+          S: 'static
 {
     type Target = T;
 
@@ -1006,8 +1472,8 @@ impl<'a, T: ?Sized, S> ops::Deref for DynRef<'a, T, S>
 } // impl Deref
 
 impl<'a, T: ?Sized, S> ops::Deref for DynRefMut<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static
+    where T: 'static,
+          S: 'static
 {
     type Target = T;
 
@@ -1015,30 +1481,128 @@ impl<'a, T: ?Sized, S> ops::Deref for DynRefMut<'a, T, S>
 } // impl Deref
 
 impl<'a, T: ?Sized, S> ops::DerefMut for DynRefMut<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static
+    where T: 'static,
+          S: 'static
 {
     fn deref_mut(&mut self) -> &mut T { self.as_trait_mut() }
 } // impl DerefMut
 
 impl<'a, T: ?Sized, S> convert::From<DynRefMut<'a, T, S>> for DynRef<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: ExtendTrait<T> + marker::Reflect + 'static,
+    where T: 'static,
+          S: ExtendTrait<T> + 'static,
 {
     fn from(r: DynRefMut<'a, T, S>) -> DynRef<'a, T, S> {
         DynRef { v_ref: r.v_ref, v_data: VData::new(r.v_data.v_offset, r.v_data.ptr) }
     }
 }
 
+//  A plain trait object stands for the whole object, hence is viewed as its `()` sub-object;
+//  from there, the struct hierarchy may be navigated with DownCast or Cast.
+//
+//  KLUDGE: these would be TryFrom implementations, were it not for the blanket
+//  `impl<T, U: Into<T>> TryFrom<U> for T`, with which they overlap.
+impl<'a, T: ?Sized> DynRef<'a, T, ()>
+    where T: PolyObject + 'static,
+{
+    //  Fails if the struct behind `it` is not registered.
+    pub fn try_of(it: &'a T) -> Result<DynRef<'a, T, ()>, RegistryError> {
+        let v_ref = VRef::try_of(it)?;
+        let root = unsafe { &*(it as *const T as *const ()) };
+
+        Ok(DynRef { v_ref, v_data: VData::new(VOffset::new(0, 0, 0), root) })
+    }
+} // impl DynRef
+
+impl<'a, T: ?Sized> DynRefMut<'a, T, ()>
+    where T: PolyObject + 'static,
+{
+    //  Fails if the struct behind `it` is not registered.
+    pub fn try_of(it: &'a mut T) -> Result<DynRefMut<'a, T, ()>, RegistryError> {
+        let v_ref = VRef::try_of(&*it)?;
+        let root = unsafe { &mut *(it as *mut T as *mut ()) };
+
+        Ok(DynRefMut { v_ref, v_data: VDataMut::new(VOffset::new(0, 0, 0), root) })
+    }
+} // impl DynRefMut
+
+//
+//  SubObjects
+//
+//  Iterator over the P sub-objects of a DynClass or DynRef, see sub_objects.
+pub struct SubObjects<'a, T: ?Sized, P>
+    where T: 'static,
+          P: 'static,
+{
+    v_ref: VRef<T>,
+    v_offset: VOffset,
+    ptr: *const u8,
+    offsets: slice::Iter<'static, isize>,
+    _0: marker::PhantomData<&'a P>,
+}
+
+impl<'a, T: ?Sized, P> SubObjects<'a, T, P>
+    where T: 'static,
+          P: 'static,
+{
+    fn new<S>(v_ref: VRef<T>, v_data: VData<'a, S>) -> SubObjects<'a, T, P>
+        where S: ExtendStruct<P> + 'static,
+    {
+        SubObjects {
+            v_ref,
+            v_offset: v_data.v_offset,
+            ptr: v_data.ptr as *const S as *const u8,
+            offsets: <S as ExtendStruct<P>>::offsets().iter(),
+            _0: marker::PhantomData,
+        }
+    }
+
+    //  Safety: `offset` is one of the offsets of the P sub-objects within S.
+    unsafe fn make(&self, offset: isize) -> DynRef<'a, T, P> {
+        let v_offset = self.v_offset.new_offset(self.v_offset.offset() + offset);
+        let ptr = &*(self.ptr.offset(offset) as *const P);
+
+        DynRef { v_ref: self.v_ref, v_data: VData::new(v_offset, ptr) }
+    }
+} // impl SubObjects
+
+impl<'a, T: ?Sized, P> Iterator for SubObjects<'a, T, P>
+    where T: 'static,
+          P: 'static,
+{
+    type Item = DynRef<'a, T, P>;
+
+    fn next(&mut self) -> Option<DynRef<'a, T, P>> {
+        let offset = *self.offsets.next()?;
+        Some(unsafe { self.make(offset) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { self.offsets.size_hint() }
+} // impl Iterator
+
+impl<'a, T: ?Sized, P> DoubleEndedIterator for SubObjects<'a, T, P>
+    where T: 'static,
+          P: 'static,
+{
+    fn next_back(&mut self) -> Option<DynRef<'a, T, P>> {
+        let offset = *self.offsets.next_back()?;
+        Some(unsafe { self.make(offset) })
+    }
+} // impl DoubleEndedIterator
+
+impl<'a, T: ?Sized, P> ExactSizeIterator for SubObjects<'a, T, P>
+    where T: 'static,
+          P: 'static,
+{}
+
 
 //
 //  Casting
 //
 impl<'a, T: ?Sized, S, B: ?Sized, P> UpCast<DynRef<'a, B, P>> for DynRef<'a, T, S>
-    where T: TraitExtendTrait<B> + marker::Reflect + 'static,
-          S: ExtendStruct<P> + marker::Reflect + 'static,
-          B: marker::Reflect + 'static,
-          P: marker::Reflect + 'static,
+    where T: TraitExtendTrait<B> + 'static,
+          S: UniqueExtendStruct<P> + 'static,
+          B: 'static,
+          P: 'static,
 {
     fn up_cast(self) -> DynRef<'a, B, P> {
         //  Compute new v_ref and offset
@@ -1051,10 +1615,10 @@ impl<'a, T: ?Sized, S, B: ?Sized, P> UpCast<DynRef<'a, B, P>> for DynRef<'a, T,
 }
 
 impl<'a, T: ?Sized, S, B: ?Sized, P> UpCast<DynRefMut<'a, B, P>> for DynRefMut<'a, T, S>
-    where T: TraitExtendTrait<B> + marker::Reflect + 'static,
-          S: ExtendStruct<P> + marker::Reflect + 'static,
-          B: marker::Reflect + 'static,
-          P: marker::Reflect + 'static,
+    where T: TraitExtendTrait<B> + 'static,
+          S: UniqueExtendStruct<P> + 'static,
+          B: 'static,
+          P: 'static,
 {
     fn up_cast(self) -> DynRefMut<'a, B, P> {
         //  Compute new v_ref and offset
@@ -1067,21 +1631,24 @@ impl<'a, T: ?Sized, S, B: ?Sized, P> UpCast<DynRefMut<'a, B, P>> for DynRefMut<'
 }
 
 impl<'a, T: ?Sized, S, D: ?Sized, C> DownCast<DynRef<'a, D, C>> for DynRef<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
-          D: TraitExtendTrait<T> + marker::Reflect + 'static,
-          C: ExtendStruct<S> + marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
+          D: TraitExtendTrait<T> + 'static,
+          C: ExtendStruct<S> + 'static,
 {
-    fn down_cast(mut self) -> Result<DynRef<'a, D, C>, DynRef<'a, T, S>> {
-        if let Some(r) = self.v_ref.down_cast() {
-            let new_v_data = self.v_data.down_cast(self.v_ref);
-
-            if let Ok(d) = new_v_data { return Ok(DynRef { v_ref: r, v_data: d }); }
+    fn down_cast(mut self) -> Result<DynRef<'a, D, C>, (DynRef<'a, T, S>, CastError)> {
+        let r = match self.v_ref.down_cast() {
+        Ok(r) => r,
+        Err(e) => return Err((self, e)),
+        };
 
-            self.v_data = new_v_data.err().unwrap();
+        match self.v_data.down_cast(self.v_ref) {
+        Ok(d) => Ok(DynRef { v_ref: r, v_data: d }),
+        Err((d, e)) => {
+            self.v_data = d;
+            Err((self, e))
+        },
         }
-
-        Err(self)
     }
 
     unsafe fn unchecked_down_cast(self) -> DynRef<'a, D, C> {
@@ -1096,21 +1663,24 @@ impl<'a, T: ?Sized, S, D: ?Sized, C> DownCast<DynRef<'a, D, C>> for DynRef<'a, T
 }
 
 impl<'a, T: ?Sized, S, D: ?Sized, C> DownCast<DynRefMut<'a, D, C>> for DynRefMut<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
-          D: TraitExtendTrait<T> + marker::Reflect + 'static,
-          C: ExtendStruct<S> + marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
+          D: TraitExtendTrait<T> + 'static,
+          C: ExtendStruct<S> + 'static,
 {
-    fn down_cast(mut self) -> Result<DynRefMut<'a, D, C>, DynRefMut<'a, T, S>> {
-        if let Some(r) = self.v_ref.down_cast() {
-            let new_v_data = self.v_data.down_cast(self.v_ref);
-
-            if let Ok(d) = new_v_data { return Ok(DynRefMut { v_ref: r, v_data: d }); }
+    fn down_cast(mut self) -> Result<DynRefMut<'a, D, C>, (DynRefMut<'a, T, S>, CastError)> {
+        let r = match self.v_ref.down_cast() {
+        Ok(r) => r,
+        Err(e) => return Err((self, e)),
+        };
 
-            self.v_data = new_v_data.err().unwrap();
+        match self.v_data.down_cast(self.v_ref) {
+        Ok(d) => Ok(DynRefMut { v_ref: r, v_data: d }),
+        Err((d, e)) => {
+            self.v_data = d;
+            Err((self, e))
+        },
         }
-
-        Err(self)
     }
 
     unsafe fn unchecked_down_cast(self) -> DynRefMut<'a, D, C> {
@@ -1125,21 +1695,24 @@ impl<'a, T: ?Sized, S, D: ?Sized, C> DownCast<DynRefMut<'a, D, C>> for DynRefMut
 }
 
 impl<'a, T: ?Sized, S, X: ?Sized, Y> Cast<DynRef<'a, X, Y>> for DynRef<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
-          X: marker::Reflect + 'static,
-          Y: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
+          X: 'static,
+          Y: 'static,
 {
-    fn cast(mut self) -> Result<DynRef<'a, X, Y>, DynRef<'a, T, S>> {
-        if let Some(r) = self.v_ref.cast() {
-            let new_v_data = self.v_data.cast(self.v_ref);
-
-            if let Ok(d) = new_v_data { return Ok(DynRef { v_ref: r, v_data: d }); }
+    fn cast(mut self) -> Result<DynRef<'a, X, Y>, (DynRef<'a, T, S>, CastError)> {
+        let r = match self.v_ref.cast() {
+        Ok(r) => r,
+        Err(e) => return Err((self, e)),
+        };
 
-            self.v_data = new_v_data.err().unwrap();
+        match self.v_data.cast(self.v_ref) {
+        Ok(d) => Ok(DynRef { v_ref: r, v_data: d }),
+        Err((d, e)) => {
+            self.v_data = d;
+            Err((self, e))
+        },
         }
-
-        Err(self)
     }
 
     unsafe fn unchecked_cast(self) -> DynRef<'a, X, Y> {
@@ -1152,21 +1725,24 @@ impl<'a, T: ?Sized, S, X: ?Sized, Y> Cast<DynRef<'a, X, Y>> for DynRef<'a, T, S>
 }
 
 impl<'a, T: ?Sized, S, X: ?Sized, Y> Cast<DynRefMut<'a, X, Y>> for DynRefMut<'a, T, S>
-    where T: marker::Reflect + 'static,
-          S: marker::Reflect + 'static,
-          X: marker::Reflect + 'static,
-          Y: marker::Reflect + 'static,
+    where T: 'static,
+          S: 'static,
+          X: 'static,
+          Y: 'static,
 {
-    fn cast(mut self) -> Result<DynRefMut<'a, X, Y>, DynRefMut<'a, T, S>> {
-        if let Some(r) = self.v_ref.cast() {
-            let new_v_data = self.v_data.cast(self.v_ref);
-
-            if let Ok(d) = new_v_data { return Ok(DynRefMut { v_ref: r, v_data: d }); }
+    fn cast(mut self) -> Result<DynRefMut<'a, X, Y>, (DynRefMut<'a, T, S>, CastError)> {
+        let r = match self.v_ref.cast() {
+        Ok(r) => r,
+        Err(e) => return Err((self, e)),
+        };
 
-            self.v_data = new_v_data.err().unwrap();
+        match self.v_data.cast(self.v_ref) {
+        Ok(d) => Ok(DynRefMut { v_ref: r, v_data: d }),
+        Err((d, e)) => {
+            self.v_data = d;
+            Err((self, e))
+        },
         }
-
-        Err(self)
     }
 
     unsafe fn unchecked_cast(self) -> DynRefMut<'a, X, Y> {
@@ -1178,3 +1754,445 @@ impl<'a, T: ?Sized, S, X: ?Sized, Y> Cast<DynRefMut<'a, X, Y>> for DynRefMut<'a,
     }
 }
 
+
+
+//
+//  RcDyn & ArcDyn (& their Weak)
+//
+//  Reference-counted thin pointers: a single allocation holds the counts, followed by the
+//  RTTI header and the data, as laid out by Class. The header describes the whole object
+//  and is shared by all clones, strong and weak; it is used to drop and deallocate it.
+//
+//  Each handle carries its own view of the object, v-table and offset, so that casting one
+//  clone does not affect the others.
+//
+//      [strong, weak][v_ref, v_offset][data...]
+//                    ^ header
+//
+#[repr(C)]
+struct Counts<C> {
+    strong: C,
+    weak: C,
+}
+
+//  Same layout as DynClass, whichever T and S.
+#[repr(C)]
+struct Header {
+    v_ref: UntypedVRef,
+    v_offset: VOffset,
+}
+
+trait Count {
+    fn one() -> Self;
+
+    fn get(&self) -> usize;
+
+    fn increment(&self);
+
+    //  Returns true if the count dropped to 0.
+    fn decrement(&self) -> bool;
+
+    //  Returns false, without incrementing, if the count is 0.
+    fn increment_if_non_zero(&self) -> bool;
+}
+
+impl Count for cell::Cell<usize> {
+    fn one() -> Self { cell::Cell::new(1) }
+
+    fn get(&self) -> usize { cell::Cell::get(self) }
+
+    fn increment(&self) { self.set(self.get() + 1); }
+
+    fn decrement(&self) -> bool {
+        self.set(self.get() - 1);
+        self.get() == 0
+    }
+
+    fn increment_if_non_zero(&self) -> bool {
+        if self.get() == 0 { return false; }
+
+        self.increment();
+        true
+    }
+}
+
+//  Same orderings as std::sync::Arc.
+impl Count for atomic::AtomicUsize {
+    fn one() -> Self { atomic::AtomicUsize::new(1) }
+
+    fn get(&self) -> usize { self.load(atomic::Ordering::Acquire) }
+
+    fn increment(&self) { self.fetch_add(1, atomic::Ordering::Relaxed); }
+
+    fn decrement(&self) -> bool {
+        if self.fetch_sub(1, atomic::Ordering::Release) != 1 { return false; }
+
+        atomic::fence(atomic::Ordering::Acquire);
+        true
+    }
+
+    fn increment_if_non_zero(&self) -> bool {
+        let mut current = self.load(atomic::Ordering::Relaxed);
+
+        while current != 0 {
+            match self.compare_exchange_weak(current, current + 1, atomic::Ordering::Acquire, atomic::Ordering::Relaxed) {
+            Ok(_) => return true,
+            Err(actual) => current = actual,
+            }
+        }
+
+        false
+    }
+}
+
+//  Layout of the allocation, and offset of the header within it; the counts immediately
+//  precede the header.
+fn shared_layout<C>(class: alloc::Layout) -> (alloc::Layout, usize) {
+    let counts = mem::size_of::<Counts<C>>();
+    let align = cmp::max(mem::align_of::<Counts<C>>(), class.align());
+
+    let offset = counts.next_multiple_of(align);
+    let layout = alloc::Layout::from_size_align(offset + class.size(), align).expect("Invalid layout");
+
+    (layout, offset)
+}
+
+//  Allocates the counts and moves `class` behind them, returns a pointer to the header.
+fn shared_new<C, T: ?Sized, S>(class: Class<T, S>) -> ptr::NonNull<Header>
+    where C: Count,
+          T: 'static,
+          S: ExtendTrait<T> + 'static,
+{
+    let (layout, offset) = shared_layout::<C>(alloc::Layout::new::<Class<T, S>>());
+
+    unsafe {
+        let raw = alloc::alloc(layout);
+        if raw.is_null() { alloc::handle_alloc_error(layout); }
+
+        let header = raw.add(offset);
+        let counts = header.sub(mem::size_of::<Counts<C>>()) as *mut Counts<C>;
+        ptr::write(counts, Counts { strong: C::one(), weak: C::one() });
+        ptr::write(header as *mut Class<T, S>, class);
+
+        ptr::NonNull::new_unchecked(header as *mut Header)
+    }
+}
+
+fn shared_counts<'a, C>(header: *const Header) -> &'a Counts<C> {
+    unsafe { &*((header as *const u8).sub(mem::size_of::<Counts<C>>()) as *const Counts<C>) }
+}
+
+//  Drops the data, once the last strong reference is gone.
+unsafe fn shared_drop_data(header: *mut Header) {
+    let Header { v_ref, v_offset } = *header;
+    v_ref.drop((header as *mut u8).offset(v_offset.base_offset()) as *mut ());
+}
+
+//  Deallocates the counts, header and data, once the last weak reference is gone.
+unsafe fn shared_deallocate<C>(header: *mut Header) {
+    let Header { v_ref, v_offset } = *header;
+
+    let (layout, offset) = shared_layout::<C>(class_layout(v_ref, v_offset));
+
+    alloc::dealloc((header as *mut u8).sub(offset), layout);
+}
+
+macro_rules! shared_dyn(
+    ($Strong:ident, $Weak:ident, $Count:ty) => {
+        pub struct $Strong<T: ?Sized, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            header: ptr::NonNull<Header>,
+            v_ref: VRef<T>,
+            v_offset: VOffset,
+            _0: marker::PhantomData<S>,
+        }
+
+        pub struct $Weak<T: ?Sized, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            header: ptr::NonNull<Header>,
+            v_ref: VRef<T>,
+            v_offset: VOffset,
+            _0: marker::PhantomData<S>,
+        }
+
+        impl<T: ?Sized, S> $Strong<T, S>
+            where T: 'static,
+                  S: ExtendTrait<T> + 'static,
+        {
+            pub fn new(class: Class<T, S>) -> $Strong<T, S> {
+                let (v_ref, v_offset) = (class.dyn_class.v_ref, class.dyn_class.v_offset);
+                let header = shared_new::<$Count, T, S>(class);
+
+                $Strong { header, v_ref, v_offset, _0: marker::PhantomData }
+            }
+        }
+
+        impl<T: ?Sized, S> $Strong<T, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            pub fn as_trait(&self) -> &T {
+                let base = self.header_ptr().wrapping_offset(self.v_offset.base_offset());
+                let raw = self.v_ref.v_table().as_trait_ptr::<T>(base as *mut ());
+                unsafe { &*raw }
+            }
+
+            pub fn as_struct(&self) -> &S {
+                let data = self.header_ptr().wrapping_offset(self.v_offset.offset());
+                unsafe { &*(data as *const S) }
+            }
+
+            pub fn as_dyn_ref(&self) -> DynRef<'_, T, S> {
+                let v_offset = VOffset::new(0, 0, self.v_offset.offset_into_struct());
+                DynRef { v_ref: self.v_ref, v_data: VData::new(v_offset, self.as_struct()) }
+            }
+
+            pub fn downgrade(this: &Self) -> $Weak<T, S> {
+                this.counts().weak.increment();
+
+                $Weak { header: this.header, v_ref: this.v_ref, v_offset: this.v_offset, _0: marker::PhantomData }
+            }
+
+            pub fn strong_count(this: &Self) -> usize { this.counts().strong.get() }
+
+            //  The implicit weak reference held by the strong references is not counted.
+            pub fn weak_count(this: &Self) -> usize { this.counts().weak.get() - 1 }
+
+            //  Whether both point to the same object, whichever their views.
+            pub fn ptr_eq<X: ?Sized, Y>(this: &Self, other: &$Strong<X, Y>) -> bool
+                where X: 'static,
+                      Y: 'static,
+            {
+                this.header == other.header
+            }
+
+            fn header_ptr(&self) -> *const u8 { self.header.as_ptr() as *const u8 }
+
+            fn counts(&self) -> &Counts<$Count> { shared_counts(self.header.as_ptr()) }
+
+            fn v_data(&self) -> VData<'_, S> { VData::new(self.v_offset, self.as_struct()) }
+
+            //  Re-targets the view, without touching the counts.
+            fn with_view<X: ?Sized, Y>(self, v_ref: VRef<X>, v_offset: VOffset) -> $Strong<X, Y>
+                where X: 'static,
+                      Y: 'static,
+            {
+                let this = mem::ManuallyDrop::new(self);
+
+                $Strong { header: this.header, v_ref, v_offset, _0: marker::PhantomData }
+            }
+        } // impl $Strong
+
+        impl<T: ?Sized, S> $Weak<T, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            //  Returns None if the object was already dropped.
+            pub fn upgrade(&self) -> Option<$Strong<T, S>> {
+                let counts: &Counts<$Count> = shared_counts(self.header.as_ptr());
+
+                if !counts.strong.increment_if_non_zero() { return None; }
+
+                Some($Strong { header: self.header, v_ref: self.v_ref, v_offset: self.v_offset, _0: marker::PhantomData })
+            }
+
+            pub fn ptr_eq<X: ?Sized, Y>(this: &Self, other: &$Weak<X, Y>) -> bool
+                where X: 'static,
+                      Y: 'static,
+            {
+                this.header == other.header
+            }
+        } // impl $Weak
+
+        impl<T: ?Sized, S> clone::Clone for $Strong<T, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            fn clone(&self) -> Self {
+                self.counts().strong.increment();
+
+                $Strong { header: self.header, v_ref: self.v_ref, v_offset: self.v_offset, _0: marker::PhantomData }
+            }
+        }
+
+        impl<T: ?Sized, S> clone::Clone for $Weak<T, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            fn clone(&self) -> Self {
+                let counts: &Counts<$Count> = shared_counts(self.header.as_ptr());
+                counts.weak.increment();
+
+                $Weak { header: self.header, v_ref: self.v_ref, v_offset: self.v_offset, _0: marker::PhantomData }
+            }
+        }
+
+        impl<T: ?Sized, S> Drop for $Strong<T, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            fn drop(&mut self) {
+                if !self.counts().strong.decrement() { return; }
+
+                unsafe { shared_drop_data(self.header.as_ptr()); }
+
+                //  The implicit weak reference held by the strong references.
+                if self.counts().weak.decrement() {
+                    unsafe { shared_deallocate::<$Count>(self.header.as_ptr()); }
+                }
+            }
+        }
+
+        impl<T: ?Sized, S> Drop for $Weak<T, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            fn drop(&mut self) {
+                let counts: &Counts<$Count> = shared_counts(self.header.as_ptr());
+
+                if counts.weak.decrement() {
+                    unsafe { shared_deallocate::<$Count>(self.header.as_ptr()); }
+                }
+            }
+        }
+
+        impl<T: ?Sized, S> ops::Deref for $Strong<T, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            type Target = T;
+
+            fn deref(&self) -> &T { self.as_trait() }
+        } // impl Deref
+
+        impl<T: ?Sized, S> fmt::Debug for $Strong<T, S>
+            where T: 'static,
+                  S: fmt::Debug + 'static,
+        {
+            fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    concat!(stringify!($Strong), " {{ v_ref: {:?}, v_offset: {:?}, data: {:?} }}"),
+                    self.v_ref,
+                    self.v_offset,
+                    self.as_struct(),
+                )
+            }
+        } // impl Debug
+
+        impl<T: ?Sized, S> fmt::Debug for $Weak<T, S>
+            where T: 'static,
+                  S: 'static,
+        {
+            fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, concat!("(", stringify!($Weak), ")"))
+            }
+        } // impl Debug
+
+        impl<T: ?Sized, S> convert::From<Class<T, S>> for $Strong<T, S>
+            where T: 'static,
+                  S: ExtendTrait<T> + 'static,
+        {
+            fn from(class: Class<T, S>) -> $Strong<T, S> { $Strong::new(class) }
+        }
+
+        impl<T: ?Sized, S, B: ?Sized, P> UpCast<$Strong<B, P>> for $Strong<T, S>
+            where T: TraitExtendTrait<B> + 'static,
+                  S: UniqueExtendStruct<P> + 'static,
+                  B: 'static,
+                  P: 'static,
+        {
+            fn up_cast(self) -> $Strong<B, P> {
+                let new_v_ref = self.v_ref.up_cast::<B>();
+
+                let new_v_data: VData<P> = self.v_data().up_cast();
+                let new_v_offset = new_v_data.v_offset;
+
+                self.with_view(new_v_ref, new_v_offset)
+            }
+        }
+
+        impl<T: ?Sized, S, D: ?Sized, C> DownCast<$Strong<D, C>> for $Strong<T, S>
+            where T: 'static,
+                  S: 'static,
+                  D: TraitExtendTrait<T> + 'static,
+                  C: ExtendStruct<S> + 'static,
+        {
+            fn down_cast(self) -> Result<$Strong<D, C>, ($Strong<T, S>, CastError)> {
+                let new_v_ref = self.v_ref.down_cast::<D>();
+
+                let new_v_offset = self.v_data().down_cast(self.v_ref).map(|vd: VData<C>| vd.v_offset);
+
+                match (new_v_ref, new_v_offset) {
+                (Ok(r), Ok(o)) => Ok(self.with_view(r, o)),
+                (Err(e), _) | (_, Err((_, e))) => Err((self, e)),
+                }
+            }
+
+            unsafe fn unchecked_down_cast(self) -> $Strong<D, C> {
+                let new_v_ref = self.v_ref.down_cast::<D>().unwrap();
+
+                let new_v_data: VData<C> = self.v_data().down_cast(self.v_ref).ok().unwrap();
+                let new_v_offset = new_v_data.v_offset;
+
+                self.with_view(new_v_ref, new_v_offset)
+            }
+        }
+
+        impl<T: ?Sized, S, X: ?Sized, Y> Cast<$Strong<X, Y>> for $Strong<T, S>
+            where T: 'static,
+                  S: 'static,
+                  X: 'static,
+                  Y: 'static,
+        {
+            fn cast(self) -> Result<$Strong<X, Y>, ($Strong<T, S>, CastError)> {
+                let new_v_ref = self.v_ref.cast::<X>();
+
+                let new_v_offset = self.v_data().cast(self.v_ref).map(|vd: VData<Y>| vd.v_offset);
+
+                match (new_v_ref, new_v_offset) {
+                (Ok(r), Ok(o)) => Ok(self.with_view(r, o)),
+                (Err(e), _) | (_, Err((_, e))) => Err((self, e)),
+                }
+            }
+
+            unsafe fn unchecked_cast(self) -> $Strong<X, Y> {
+                let new_v_ref = self.v_ref.cast::<X>().unwrap();
+
+                let new_v_data: VData<Y> = self.v_data().cast(self.v_ref).ok().unwrap();
+                let new_v_offset = new_v_data.v_offset;
+
+                self.with_view(new_v_ref, new_v_offset)
+            }
+        }
+    };
+);
+
+shared_dyn!(RcDyn, WeakRcDyn, cell::Cell<usize>);
+shared_dyn!(ArcDyn, WeakArcDyn, atomic::AtomicUsize);
+
+//  The whole object may be reached through T, and the S sub-object directly, which must
+//  therefore both be Send and Sync, as for std::sync::Arc<dyn T>.
+unsafe impl<T: ?Sized, S> Send for ArcDyn<T, S>
+    where T: Send + Sync + 'static,
+          S: Send + Sync + 'static,
+{}
+
+unsafe impl<T: ?Sized, S> Sync for ArcDyn<T, S>
+    where T: Send + Sync + 'static,
+          S: Send + Sync + 'static,
+{}
+
+unsafe impl<T: ?Sized, S> Send for WeakArcDyn<T, S>
+    where T: Send + Sync + 'static,
+          S: Send + Sync + 'static,
+{}
+
+unsafe impl<T: ?Sized, S> Sync for WeakArcDyn<T, S>
+    where T: Send + Sync + 'static,
+          S: Send + Sync + 'static,
+{}
@@ -0,0 +1,144 @@
+//
+//  A BoxDyn frees its allocation with the layout it was allocated with, whichever its view.
+//
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::thread::LocalKey;
+
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{BoxDyn, Class, UpCast};
+
+#[poly_trait]
+trait Node: RawClone {}
+
+#[poly_trait]
+trait Element: Node {}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct NodeData {
+    id: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct ElementData {
+    #[parent]
+    node: NodeData,
+    children: Vec<u32>,
+}
+
+impl Node for NodeData {}
+impl Node for ElementData {}
+
+impl Element for ElementData {}
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct NodeData impl Node;
+    #[derive(PolyStruct)] struct ElementData impl Element;
+}
+
+fn element(children: Vec<u32>) -> ElementData {
+    ElementData { node: NodeData { id: 1 }, children }
+}
+
+//  Records the allocations and deallocations of the current thread, while tracking.
+struct Ledger;
+
+#[global_allocator]
+static LEDGER: Ledger = Ledger;
+
+const CAPACITY: usize = 8;
+
+//  The address and layout of an allocation.
+type Event = (usize, Layout);
+
+type Events = [Option<Event>; CAPACITY];
+
+//  Tests run concurrently, each on its own thread; recording must not allocate.
+thread_local! {
+    static TRACKING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATED: Cell<Events> = const { Cell::new([None; CAPACITY]) };
+    static FREED: Cell<Events> = const { Cell::new([None; CAPACITY]) };
+}
+
+fn record(events: &'static LocalKey<Cell<Events>>, ptr: *mut u8, layout: Layout) {
+    if !TRACKING.with(Cell::get) { return; }
+
+    events.with(|events| {
+        let mut list = events.get();
+
+        //  Overflows show up as a mismatch between allocations and deallocations.
+        if let Some(slot) = list.iter_mut().find(|e| e.is_none()) {
+            *slot = Some((ptr as usize, layout));
+        }
+
+        events.set(list);
+    });
+}
+
+unsafe impl GlobalAlloc for Ledger {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        record(&ALLOCATED, ptr, layout);
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(&FREED, ptr, layout);
+        System.dealloc(ptr, layout);
+    }
+}
+
+//  The allocations and deallocations performed by `f`, sorted by address.
+fn track<F>(f: F) -> (Vec<Event>, Vec<Event>)
+    where F: FnOnce()
+{
+    //  Builds the registries, which are never freed, if no other test did.
+    assert!(poly::try_struct_info::<ElementData>().is_ok());
+
+    ALLOCATED.with(|e| e.set([None; CAPACITY]));
+    FREED.with(|e| e.set([None; CAPACITY]));
+
+    TRACKING.with(|t| t.set(true));
+    f();
+    TRACKING.with(|t| t.set(false));
+
+    let sorted = |events: &'static LocalKey<Cell<Events>>| {
+        let mut list: Vec<_> = events.with(Cell::get).iter().flatten().copied().collect();
+        list.sort_by_key(|e| e.0);
+        list
+    };
+
+    (sorted(&ALLOCATED), sorted(&FREED))
+}
+
+#[test]
+fn drop_through_parent() {
+    let (allocated, freed) = track(|| {
+        let element: BoxDyn<dyn Element, ElementData> = BoxDyn::new(Class::new(element(vec!())));
+        let node: BoxDyn<dyn Node, NodeData> = element.up_cast();
+        drop(node);
+    });
+
+    let layouts: Vec<_> = allocated.iter().map(|e| e.1).collect();
+    assert_eq!(layouts, vec!(Layout::new::<Class<dyn Element, ElementData>>()));
+    assert_eq!(freed, allocated);
+}
+
+#[test]
+fn clone_through_parent() {
+    let element: BoxDyn<dyn Element, ElementData> = BoxDyn::new(Class::new(element(vec!(1, 2))));
+    let node: BoxDyn<dyn Node, NodeData> = element.up_cast();
+
+    let (allocated, freed) = track(|| {
+        let copy = node.clone_to_box();
+        drop(copy);
+    });
+
+    //  The whole ElementData is cloned, children included, not merely its NodeData.
+    let class = Layout::new::<Class<dyn Element, ElementData>>();
+    assert_eq!(allocated.len(), 2, "{:?}", allocated);
+    assert!(allocated.iter().any(|e| e.1 == class), "{:?}", allocated);
+    assert_eq!(freed, allocated);
+}
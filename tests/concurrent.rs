@@ -0,0 +1,93 @@
+//
+//  The registries are built on first use, possibly from many threads at once.
+//
+use std::sync::Barrier;
+use std::thread;
+
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{BoxDyn, Class, DownCast, UpCast};
+
+#[poly_trait]
+trait Shape: RawClone {
+    fn sides(&self) -> usize;
+}
+
+#[poly_trait]
+trait Polygon: Shape {
+    fn name(&self) -> &'static str;
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct ShapeData {
+    id: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Square {
+    #[parent]
+    _first_parent: ShapeData,
+    side: u32,
+}
+
+impl Shape for ShapeData {
+    fn sides(&self) -> usize { 0 }
+}
+
+impl Shape for Square {
+    fn sides(&self) -> usize { 4 }
+}
+
+impl Polygon for Square {
+    fn name(&self) -> &'static str { "square" }
+}
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct ShapeData impl Shape;
+    #[derive(PolyStruct)] struct Square impl Polygon;
+}
+
+#[test]
+fn concurrent_initialization_and_casts() {
+    const THREADS: usize = 16;
+    const ITERATIONS: usize = 1_000;
+
+    let barrier = Barrier::new(THREADS);
+
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let barrier = &barrier;
+
+            scope.spawn(move || {
+                //  All threads race to build the registries.
+                barrier.wait();
+
+                for i in 0..ITERATIONS {
+                    let id = t * ITERATIONS + i;
+                    let square = Square { _first_parent: ShapeData { id }, side: i as u32 };
+
+                    let square: BoxDyn<dyn Polygon, Square> = BoxDyn::new(Class::new(square));
+                    assert_eq!(square.as_trait().name(), "square");
+
+                    let shape: BoxDyn<dyn Shape, ShapeData> = square.up_cast();
+                    assert_eq!(shape.as_trait().sides(), 4);
+                    assert_eq!(shape.as_struct().id, id);
+
+                    let square: BoxDyn<dyn Polygon, Square> =
+                        shape.down_cast().expect("down_cast to Square");
+                    assert_eq!(square.as_struct().side, i as u32);
+
+                    let plain: BoxDyn<dyn Shape, ShapeData> =
+                        BoxDyn::new(Class::new(ShapeData { id }));
+                    let result: Result<BoxDyn<dyn Polygon, Square>, _> = plain.down_cast();
+                    assert!(result.is_err());
+                }
+            });
+        }
+    });
+
+    assert!(poly::try_struct_info::<Square>().is_ok());
+    assert!(poly::try_trait_info::<dyn Polygon>().is_ok());
+    assert!(poly::try_v_table::<dyn Shape, Square>().is_ok());
+}
@@ -0,0 +1,99 @@
+//
+//  Existing references to trait objects may be converted into DynRef, and from there navigate
+//  the struct hierarchy.
+//
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{Cast, DownCast, DynRef, DynRefMut, RegistryError};
+
+#[poly_trait]
+trait Node: RawClone {
+    fn name(&self) -> String;
+}
+
+#[poly_trait]
+trait Element: Node {
+    fn tag(&self) -> &'static str;
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct NodeData {
+    id: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct ElementData {
+    #[parent]
+    node: NodeData,
+    attributes: Vec<(String, String)>,
+}
+
+//  Implements Node, but is not registered.
+#[derive(Clone, Debug)]
+struct Stranger;
+
+impl Node for NodeData { fn name(&self) -> String { format!("#node-{}", self.id) } }
+impl Node for ElementData { fn name(&self) -> String { format!("div-{}", self.node.id) } }
+impl Node for Stranger { fn name(&self) -> String { "stranger".to_string() } }
+
+impl Element for ElementData { fn tag(&self) -> &'static str { "div" } }
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct NodeData impl Node;
+    #[derive(PolyStruct)] struct ElementData impl Element;
+}
+
+fn element(id: u32) -> ElementData {
+    ElementData { node: NodeData { id }, attributes: vec!(("class".to_string(), "x".to_string())) }
+}
+
+#[test]
+fn from_shared_reference() {
+    let element = element(3);
+    let node: &dyn Node = &element;
+
+    let r: DynRef<dyn Node, ()> = DynRef::try_of(node).expect("registered struct");
+    assert_eq!(r.as_trait().name(), "div-3");
+
+    let r: DynRef<dyn Element, ElementData> = r.down_cast().ok().expect("down_cast to ElementData");
+    assert_eq!(r.as_trait().tag(), "div");
+    assert_eq!(r.as_struct().attributes[0].1, "x");
+
+    let r: DynRef<dyn Node, NodeData> = DynRef::try_of(node).expect("registered struct").cast().ok().expect("cast to NodeData");
+    assert_eq!(r.as_struct().id, 3);
+
+    let data = NodeData { id: 4 };
+    let node: &dyn Node = &data;
+
+    let result: Result<DynRef<dyn Element, ElementData>, _> = DynRef::try_of(node).expect("registered struct").down_cast();
+    assert!(result.is_err());
+}
+
+#[test]
+fn from_mutable_reference() {
+    let mut element = element(5);
+
+    {
+        let node: &mut dyn Node = &mut element;
+
+        let r: DynRefMut<dyn Node, ()> = DynRefMut::try_of(node).expect("registered struct");
+        let mut r: DynRefMut<dyn Node, NodeData> = r.down_cast().ok().expect("down_cast to NodeData");
+        r.as_struct_mut().id = 6;
+
+        assert_eq!(r.as_trait().name(), "div-6");
+    }
+
+    assert_eq!(element.node.id, 6);
+}
+
+#[test]
+fn from_unregistered_struct() {
+    let mut stranger = Stranger;
+
+    let node: &dyn Node = &stranger;
+    assert!(matches!(DynRef::try_of(node).err(), Some(RegistryError::MissingImpl { .. })));
+
+    let node: &mut dyn Node = &mut stranger;
+    assert!(matches!(DynRefMut::try_of(node).err(), Some(RegistryError::MissingImpl { .. })));
+}
@@ -0,0 +1,129 @@
+//
+//  Offsets and marker traits of #[derive(PolyStruct)], through several levels of parents.
+//
+use std::marker::PhantomData;
+
+use poly::{ExtendStruct, FirstExtendStruct, PolyStruct};
+
+#[repr(C)]
+#[derive(PolyStruct)]
+struct Root {
+    id: u32,
+}
+
+//  Root is not the first field, hence not at offset 0.
+#[repr(C)]
+#[derive(PolyStruct)]
+struct Middle {
+    tag: u64,
+    #[parent]
+    root: Root,
+}
+
+#[repr(C)]
+#[derive(PolyStruct)]
+struct Leaf {
+    flag: u8,
+    #[parent]
+    middle: Middle,
+}
+
+//  Root is the first field, transitively.
+#[repr(C)]
+#[derive(PolyStruct)]
+struct Head {
+    #[parent]
+    root: Root,
+    tag: u64,
+}
+
+#[repr(C)]
+#[derive(PolyStruct)]
+struct Chain {
+    #[parent]
+    head: Head,
+    flag: u8,
+}
+
+//  Root is the first field, but without #[repr(C)] its offset is not guaranteed.
+#[derive(PolyStruct)]
+struct Loose {
+    #[parent]
+    root: Root,
+    tag: u64,
+}
+
+//  The P sub-objects of `s`, as located by ExtendStruct.
+fn sub_objects<S, P>(s: &S) -> Vec<&P>
+    where S: ExtendStruct<P>
+{
+    let base = s as *const S as *const u8;
+
+    S::offsets().iter().map(|&o| unsafe { &*(base.offset(o) as *const P) }).collect()
+}
+
+//  Whether S implements FirstExtendStruct<P>: the inherent constant, only available when it
+//  does, shadows the one of the trait.
+struct Probe<S, P>(PhantomData<(S, P)>);
+
+trait NotFirst {
+    const FIRST: bool = false;
+}
+
+impl<S, P> NotFirst for Probe<S, P> {}
+
+impl<S, P> Probe<S, P>
+    where S: FirstExtendStruct<P>
+{
+    const FIRST: bool = true;
+}
+
+fn leaf() -> Leaf {
+    Leaf { flag: 1, middle: Middle { tag: 2, root: Root { id: 3 } } }
+}
+
+#[test]
+fn non_first_parents() {
+    let leaf = leaf();
+
+    let offset = std::mem::offset_of!(Leaf, middle) + std::mem::offset_of!(Middle, root);
+    assert_ne!(offset, 0);
+    assert_eq!(<Leaf as ExtendStruct<Root>>::offsets(), [offset as isize]);
+
+    assert_eq!(sub_objects::<Leaf, Root>(&leaf).iter().map(|r| r.id).collect::<Vec<_>>(), vec!(3));
+    assert_eq!(sub_objects::<Leaf, Middle>(&leaf).iter().map(|m| m.tag).collect::<Vec<_>>(), vec!(2));
+    assert_eq!(sub_objects::<Leaf, Leaf>(&leaf).iter().map(|l| l.flag).collect::<Vec<_>>(), vec!(1));
+}
+
+#[test]
+fn first_parents() {
+    let chain = Chain { head: Head { root: Root { id: 4 }, tag: 5 }, flag: 6 };
+
+    assert_eq!(<Chain as ExtendStruct<Root>>::offsets(), [0]);
+    assert_eq!(<Chain as ExtendStruct<Head>>::offsets(), [0]);
+
+    assert_eq!(sub_objects::<Chain, Root>(&chain)[0].id, 4);
+    assert_eq!(sub_objects::<Chain, Head>(&chain)[0].tag, 5);
+    assert_eq!(chain.flag, 6);
+
+    let loose = Loose { root: Root { id: 7 }, tag: 8 };
+
+    assert_eq!(sub_objects::<Loose, Root>(&loose)[0].id, 7);
+    assert_eq!(loose.tag, 8);
+}
+
+//  Only the first field of a #[repr(C)] struct, transitively; checked at compile-time.
+const _: () = {
+    assert!(Probe::<Chain, Head>::FIRST);
+    assert!(Probe::<Chain, Root>::FIRST);
+    assert!(Probe::<Head, Root>::FIRST);
+
+    assert!(!Probe::<Middle, Root>::FIRST);
+    assert!(!Probe::<Leaf, Middle>::FIRST);
+    assert!(!Probe::<Leaf, Root>::FIRST);
+    assert!(!Probe::<Loose, Root>::FIRST);
+
+    //  Every struct is its own first parent, and that of ().
+    assert!(Probe::<Leaf, Leaf>::FIRST);
+    assert!(Probe::<Loose, ()>::FIRST);
+};
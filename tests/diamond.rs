@@ -0,0 +1,215 @@
+//
+//  Parent ambiguity: Bottom contains two Base, one through Left and one through Right.
+//
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{BoxDyn, CastError, Class, DynRef, DynRefMut, DownCast, ExtendStruct};
+
+#[poly_trait]
+trait Thing: RawClone {
+    fn name(&self) -> &'static str;
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Base {
+    id: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Left {
+    #[parent]
+    base: Base,
+    left: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Right {
+    #[parent]
+    base: Base,
+    right: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Bottom {
+    #[parent]
+    left: Left,
+    #[parent]
+    right: Right,
+    bottom: u32,
+}
+
+//  Reaches Base as `Base` through Left, and as `self::Base` through Spelled.
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Spelled {
+    #[parent]
+    base: self::Base,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Skewed {
+    #[parent]
+    left: Left,
+    #[parent]
+    spelled: Spelled,
+}
+
+impl Thing for Base { fn name(&self) -> &'static str { "base" } }
+impl Thing for Left { fn name(&self) -> &'static str { "left" } }
+impl Thing for Right { fn name(&self) -> &'static str { "right" } }
+impl Thing for Bottom { fn name(&self) -> &'static str { "bottom" } }
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct Base impl Thing;
+    #[derive(PolyStruct)] struct Left impl Thing;
+    #[derive(PolyStruct)] struct Right impl Thing;
+    #[derive(PolyStruct)] struct Bottom impl Thing;
+}
+
+fn bottom() -> BoxDyn<dyn Thing, Bottom> {
+    let bottom = Bottom {
+        left: Left { base: Base { id: 1 }, left: 10 },
+        right: Right { base: Base { id: 2 }, right: 20 },
+        bottom: 30,
+    };
+
+    BoxDyn::new(Class::new(bottom))
+}
+
+#[test]
+fn offsets() {
+    let offsets = <Bottom as ExtendStruct<Base>>::offsets();
+
+    assert_eq!(offsets.len(), 2);
+    assert!(offsets[0] < offsets[1]);
+    assert_eq!(offsets[0], 0);
+}
+
+#[test]
+fn differently_spelled_ancestor() {
+    let offsets = <Skewed as ExtendStruct<Base>>::offsets();
+
+    assert_eq!(offsets, [0, std::mem::offset_of!(Skewed, spelled) as isize]);
+}
+
+#[test]
+fn ambiguous_up_cast() {
+    let bottom = bottom();
+    let r = DynRef::new::<()>(&*bottom);
+
+    assert_eq!(r.try_up_cast::<Base>().err(), Some(CastError::Ambiguous { candidates: 2 }));
+    assert_eq!(r.try_up_cast::<Right>().map(|r| r.as_struct().right).ok(), Some(20));
+
+    let ids: Vec<_> = r.up_cast_all::<Base>().iter().map(|b| b.as_struct().id).collect();
+    assert_eq!(ids, vec!(1, 2));
+
+    let base = r.clone().up_cast_via::<Right, Base>();
+    assert_eq!(base.as_struct().id, 2);
+    assert_eq!(base.as_trait().name(), "bottom");
+
+    let base = r.up_cast_via::<Left, Base>();
+    assert_eq!(base.as_struct().id, 1);
+}
+
+#[test]
+fn ambiguous_cross_cast() {
+    let bottom = bottom();
+    let r = DynRef::new::<()>(&*bottom);
+
+    assert_eq!(r.try_cast::<dyn Thing, Base>().err(), Some(CastError::Ambiguous { candidates: 2 }));
+    assert_eq!(r.try_cast::<dyn Thing, Right>().map(|r| r.as_struct().right).ok(), Some(20));
+}
+
+#[test]
+fn down_cast_from_either_branch() {
+    let bottom = bottom();
+
+    //  Each Base knows which branch it belongs to.
+    for (via_right, expected) in [(false, 10), (true, 20)] {
+        let r = DynRef::new::<()>(&*bottom);
+
+        let base = if via_right {
+            r.up_cast_via::<Right, Base>()
+        } else {
+            r.up_cast_via::<Left, Base>()
+        };
+
+        let back: DynRef<dyn Thing, Bottom> = base.clone().down_cast().ok().expect("down_cast to Bottom");
+        assert_eq!(back.as_struct().bottom, 30);
+
+        let branch = if via_right {
+            let right: DynRef<dyn Thing, Right> = base.down_cast().ok().expect("down_cast to Right");
+            right.as_struct().right
+        } else {
+            let left: DynRef<dyn Thing, Left> = base.down_cast().ok().expect("down_cast to Left");
+            left.as_struct().left
+        };
+        assert_eq!(branch, expected);
+    }
+
+    //  Through a BoxDyn, too.
+    let base = bottom.up_cast_via::<Right, Base>();
+    assert_eq!(base.as_struct().id, 2);
+
+    let right: BoxDyn<dyn Thing, Right> = base.down_cast().expect("down_cast to Right");
+    assert_eq!(right.as_struct().right, 20);
+    assert_eq!(right.as_trait().name(), "bottom");
+}
+
+#[test]
+fn cast_paths() {
+    let mut bottom = bottom();
+
+    {
+        let r = DynRef::new::<()>(&*bottom);
+
+        assert_eq!(r.clone().up_cast_path::<(Left, Base)>().as_struct().id, 1);
+        assert_eq!(r.clone().up_cast_path::<(Right, Base)>().as_struct().id, 2);
+        assert_eq!(r.up_cast_path::<(Right,)>().as_struct().right, 20);
+    }
+
+    {
+        let mut r = DynRefMut::new::<()>(&mut *bottom).up_cast_path::<(Right, Base)>();
+        r.as_struct_mut().id = 42;
+        assert_eq!(r.as_trait().name(), "bottom");
+    }
+
+    assert_eq!(bottom.as_struct().right.base.id, 42);
+
+    let base = bottom.up_cast_path::<(Left, Base)>();
+    assert_eq!(base.as_struct().id, 1);
+
+    let left: BoxDyn<dyn Thing, Left> = base.down_cast().expect("down_cast to Left");
+    assert_eq!(left.as_struct().left, 10);
+}
+
+#[test]
+fn sub_objects() {
+    let bottom = bottom();
+
+    let ids: Vec<_> = bottom.sub_objects::<Base>().map(|b| b.as_struct().id).collect();
+    assert_eq!(ids, vec!(1, 2));
+
+    let ids: Vec<_> = bottom.sub_objects::<Base>().rev().map(|b| b.as_struct().id).collect();
+    assert_eq!(ids, vec!(2, 1));
+
+    assert_eq!(bottom.sub_objects::<Base>().len(), 2);
+    assert_eq!(bottom.sub_objects::<Right>().len(), 1);
+
+    //  Each sub-object remains a view of the whole.
+    for base in bottom.sub_objects::<Base>() {
+        assert_eq!(base.as_trait().name(), "bottom");
+
+        let back: DynRef<dyn Thing, Bottom> = base.down_cast().ok().expect("down_cast to Bottom");
+        assert_eq!(back.as_struct().bottom, 30);
+    }
+
+    //  Only the sub-objects of the static struct are visited.
+    let right = DynRef::new::<()>(&*bottom).up_cast_path::<(Right,)>();
+    let ids: Vec<_> = right.sub_objects::<Base>().map(|b| b.as_struct().id).collect();
+    assert_eq!(ids, vec!(2));
+}
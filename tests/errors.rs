@@ -0,0 +1,91 @@
+//
+//  Failed look-ups and casts tell why they failed.
+//
+use std::sync::Mutex;
+
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{BoxDyn, Cast, CastError, Class, RegistryError, Registration};
+use poly::{StructId, StructInfo, TraitId, TraitInfo};
+use poly::__private::{self, VTableRegistryIndices, VTableRegistryTables};
+
+#[poly_trait]
+trait Shape: RawClone {}
+
+//  Registered, yet not implemented by Circle.
+#[poly_trait]
+trait Polygon: Shape {}
+
+//  Never registered.
+trait Stranger {}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Circle {
+    radius: u32,
+}
+
+impl Shape for Circle {}
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct Circle impl Shape;
+}
+
+//  A registration function looking up its own registry, while it is being built.
+static OBSERVED: Mutex<Option<Result<(), RegistryError>>> = Mutex::new(None);
+
+fn observe_struct_info(_: &mut Vec<(StructId, StructInfo)>) {
+    let result = poly::try_struct_info::<Circle>().map(|_| ());
+
+    *OBSERVED.lock().unwrap() = Some(result);
+}
+
+fn no_trait_info(_: &mut Vec<(TraitId, TraitInfo)>) {}
+
+fn no_v_tables(_: &mut VTableRegistryTables, _: &mut VTableRegistryIndices) {}
+
+__private::inventory::submit! {
+    Registration::new(observe_struct_info, no_trait_info, no_v_tables)
+}
+
+fn circle() -> BoxDyn<dyn Shape, Circle> {
+    BoxDyn::new(Class::new(Circle { radius: 1 }))
+}
+
+#[test]
+fn missing_impl() {
+    let trait_id = poly::trait_id::<dyn Polygon>();
+    let struct_id = poly::struct_id::<Circle>();
+
+    let error = poly::try_v_table::<dyn Polygon, Circle>().err();
+    assert_eq!(error, Some(RegistryError::MissingImpl { trait_id, struct_id }));
+
+    let message = error.unwrap().to_string();
+    assert!(message.contains("Circle") && message.contains("Polygon"), "{}", message);
+
+    //  Not implementing a registered trait is no registry error, merely a Mismatch.
+    let result: Result<BoxDyn<dyn Polygon, Circle>, _> = circle().cast();
+    assert_eq!(result.err().map(|(c, e)| (c.as_struct().radius, e)), Some((1, CastError::Mismatch)));
+}
+
+#[test]
+fn unknown_trait() {
+    let trait_id = poly::trait_id::<dyn Stranger>();
+
+    let error = poly::try_trait_info::<dyn Stranger>().err();
+    assert_eq!(error, Some(RegistryError::UnknownTrait(trait_id)));
+
+    let message = error.unwrap().to_string();
+    assert!(message.contains("Stranger"), "{}", message);
+
+    let result: Result<BoxDyn<dyn Stranger, Circle>, _> = circle().cast();
+    let error = result.err().map(|(_, e)| e);
+    assert_eq!(error, Some(CastError::Registry(RegistryError::UnknownTrait(trait_id))));
+}
+
+#[test]
+fn not_initialized() {
+    //  Builds the registries, if no other test did.
+    assert!(poly::try_struct_info::<Circle>().is_ok());
+
+    assert_eq!(*OBSERVED.lock().unwrap(), Some(Err(RegistryError::NotInitialized)));
+}
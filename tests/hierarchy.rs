@@ -0,0 +1,98 @@
+//
+//  Hierarchies declared through `poly_hierarchy!` alone, without derive nor attribute.
+//
+use std::mem;
+
+use poly::{poly_hierarchy, RawClone};
+use poly::{BoxDyn, Class, DownCast, ExtendStruct, FirstExtendStruct, TraitExtendTrait, UpCast, VTable};
+
+trait Shape: RawClone {
+    fn sides(&self) -> usize;
+}
+
+trait Polygon: Shape {
+    fn name(&self) -> &'static str;
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+struct ShapeData {
+    id: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+struct PolygonData {
+    _first_parent: ShapeData,
+    sides: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+struct Square {
+    _first_parent: PolygonData,
+    side: u32,
+}
+
+impl Shape for ShapeData { fn sides(&self) -> usize { 0 } }
+impl Shape for PolygonData { fn sides(&self) -> usize { self.sides } }
+impl Shape for Square { fn sides(&self) -> usize { 4 } }
+
+impl Polygon for PolygonData { fn name(&self) -> &'static str { "polygon" } }
+impl Polygon for Square { fn name(&self) -> &'static str { "square" } }
+
+poly_hierarchy! {
+    trait Shape;
+    trait Polygon: Shape;
+
+    struct ShapeData impl Shape;
+    struct PolygonData: ShapeData impl Polygon;
+    struct Square: PolygonData: ShapeData impl Polygon;
+}
+
+fn first<S, P>() -> &'static [isize]
+    where S: FirstExtendStruct<P>
+{
+    <S as ExtendStruct<P>>::offsets()
+}
+
+fn square() -> BoxDyn<dyn Polygon, Square> {
+    let polygon = PolygonData { _first_parent: ShapeData { id: 1 }, sides: 4 };
+
+    BoxDyn::new(Class::new(Square { _first_parent: polygon, side: 2 }))
+}
+
+#[test]
+fn struct_parents() {
+    assert_eq!(first::<Square, Square>(), [0]);
+    assert_eq!(first::<Square, PolygonData>(), [0]);
+    assert_eq!(first::<Square, ShapeData>(), [0]);
+    assert_eq!(first::<Square, ()>(), [0]);
+
+    let info = poly::struct_info::<Square>();
+    assert_eq!(info.size(), mem::size_of::<Square>());
+}
+
+#[test]
+fn trait_parents() {
+    let offset = <dyn Polygon as TraitExtendTrait<dyn Shape>>::offset();
+    assert_eq!(offset, mem::size_of::<VTable>() as isize);
+
+    let v_table = poly::v_table::<dyn Shape, Square>();
+    assert_eq!(v_table.trait_info().trait_id(), poly::trait_id::<dyn Shape>());
+    assert_eq!(v_table.struct_info().struct_id(), poly::struct_id::<Square>());
+}
+
+#[test]
+fn casts() {
+    let shape: BoxDyn<dyn Shape, ShapeData> = square().up_cast();
+    assert_eq!(shape.as_trait().sides(), 4);
+    assert_eq!(shape.as_struct().id, 1);
+
+    let polygon: BoxDyn<dyn Polygon, PolygonData> = shape.down_cast().expect("down_cast to PolygonData");
+    assert_eq!(polygon.as_trait().name(), "square");
+    assert_eq!(polygon.as_struct().sides, 4);
+
+    let square: BoxDyn<dyn Polygon, Square> = polygon.down_cast().expect("down_cast to Square");
+    assert_eq!(square.as_struct().side, 2);
+}
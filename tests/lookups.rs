@@ -0,0 +1,61 @@
+//
+//  Look-ups by id find each registered entry, and only those.
+//
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{RegistryError, StructId};
+
+#[poly_trait]
+trait Item: RawClone {}
+
+//  Implements Item, but is not registered.
+#[derive(Clone, Debug)]
+struct Stranger;
+
+impl Item for Stranger {}
+
+macro_rules! items {
+    ($($S:ident)*) => {
+        $(
+            #[repr(C)]
+            #[derive(Clone, Debug, PolyStruct)]
+            struct $S;
+
+            impl Item for $S {}
+        )*
+
+        poly_hierarchy! {
+            $( #[derive(PolyStruct)] struct $S impl Item; )*
+        }
+
+        fn item_ids() -> Vec<StructId> { vec!($(poly::struct_id::<$S>()),*) }
+    };
+}
+
+items! {
+    I00 I01 I02 I03 I04 I05 I06 I07 I08 I09 I10 I11 I12 I13 I14 I15
+    I16 I17 I18 I19 I20 I21 I22 I23 I24 I25 I26 I27 I28 I29 I30 I31
+}
+
+#[test]
+fn registered_entries() {
+    let trait_id = poly::trait_id::<dyn Item>();
+
+    assert_eq!(poly::trait_info_by_id(trait_id).trait_id(), trait_id);
+
+    for struct_id in item_ids() {
+        assert_eq!(poly::struct_info_by_id(struct_id).struct_id(), struct_id);
+
+        let v_table = poly::v_table_by_id(trait_id, struct_id).expect("registered v-table");
+        assert_eq!(v_table.struct_info().struct_id(), struct_id);
+        assert_eq!(v_table.trait_info().trait_id(), trait_id);
+    }
+}
+
+#[test]
+fn unregistered_entries() {
+    let trait_id = poly::trait_id::<dyn Item>();
+    let struct_id = poly::struct_id::<Stranger>();
+
+    assert_eq!(poly::try_struct_info_by_id(struct_id).err(), Some(RegistryError::UnknownStruct(struct_id)));
+    assert!(poly::v_table_by_id(trait_id, struct_id).is_none());
+}
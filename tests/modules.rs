@@ -0,0 +1,81 @@
+//
+//  Each module declares its own part of the hierarchy; all parts are collected at link-time.
+//
+use poly::{BoxDyn, Class, DownCast, UpCast};
+
+mod shapes {
+    use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+
+    #[poly_trait]
+    pub trait Shape: RawClone {
+        fn sides(&self) -> usize;
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Debug, PolyStruct)]
+    pub struct ShapeData {
+        pub id: u32,
+    }
+
+    impl Shape for ShapeData { fn sides(&self) -> usize { 0 } }
+
+    poly_hierarchy! {
+        #[derive(PolyStruct)] struct ShapeData impl Shape;
+    }
+}
+
+mod polygons {
+    use poly::{poly_hierarchy, poly_trait, PolyStruct};
+
+    use super::shapes::{self, Shape};
+
+    //  The parents are spelled through their module, along with their hidden macros.
+    #[poly_trait]
+    pub trait Polygon: shapes::Shape {
+        fn name(&self) -> &'static str;
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Debug, PolyStruct)]
+    pub struct Square {
+        #[parent]
+        pub shape: shapes::ShapeData,
+        pub side: u32,
+    }
+
+    impl Shape for Square { fn sides(&self) -> usize { 4 } }
+    impl Polygon for Square { fn name(&self) -> &'static str { "square" } }
+
+    poly_hierarchy! {
+        #[derive(PolyStruct)] struct Square impl Polygon;
+    }
+}
+
+use polygons::{Polygon, Square};
+use shapes::{Shape, ShapeData};
+
+#[test]
+fn registered() {
+    assert!(poly::try_struct_info::<ShapeData>().is_ok());
+    assert!(poly::try_struct_info::<Square>().is_ok());
+
+    assert!(poly::try_trait_info::<dyn Shape>().is_ok());
+    assert!(poly::try_trait_info::<dyn Polygon>().is_ok());
+
+    assert!(poly::try_v_table::<dyn Shape, ShapeData>().is_ok());
+    assert!(poly::try_v_table::<dyn Shape, Square>().is_ok());
+}
+
+#[test]
+fn casts_across_modules() {
+    let square = Square { shape: ShapeData { id: 1 }, side: 2 };
+    let square: BoxDyn<dyn Polygon, Square> = BoxDyn::new(Class::new(square));
+
+    let shape: BoxDyn<dyn Shape, ShapeData> = square.up_cast();
+    assert_eq!(shape.as_trait().sides(), 4);
+    assert_eq!(shape.as_struct().id, 1);
+
+    let square: BoxDyn<dyn Polygon, Square> = shape.down_cast().expect("down_cast to Square");
+    assert_eq!(square.as_trait().name(), "square");
+    assert_eq!(square.as_struct().side, 2);
+}
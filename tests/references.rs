@@ -0,0 +1,103 @@
+//
+//  Plain `&dyn T` and `&mut dyn T` may be cast, as long as the struct behind is registered.
+//
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{Cast, CastError, DownCast, UpCast};
+
+#[poly_trait]
+trait Shape: RawClone {
+    fn sides(&self) -> usize;
+}
+
+#[poly_trait]
+trait Polygon: Shape {
+    fn name(&self) -> &'static str;
+    fn grow(&mut self);
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Circle {
+    radius: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Square {
+    side: u32,
+}
+
+//  Implements Shape, but is not registered.
+#[derive(Clone, Debug)]
+struct Stranger;
+
+impl Shape for Circle { fn sides(&self) -> usize { 0 } }
+impl Shape for Square { fn sides(&self) -> usize { 4 } }
+impl Shape for Stranger { fn sides(&self) -> usize { 7 } }
+
+impl Polygon for Square {
+    fn name(&self) -> &'static str { "square" }
+    fn grow(&mut self) { self.side += 1; }
+}
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct Circle impl Shape;
+    #[derive(PolyStruct)] struct Square impl Polygon;
+}
+
+#[test]
+fn shared_references() {
+    let square = Square { side: 2 };
+    let polygon: &dyn Polygon = &square;
+
+    let shape: &dyn Shape = polygon.up_cast();
+    assert_eq!(shape.sides(), 4);
+
+    let polygon: &dyn Polygon = shape.down_cast().ok().expect("down_cast to Polygon");
+    assert_eq!(polygon.name(), "square");
+
+    let polygon: &dyn Polygon = shape.cast().ok().expect("cast to Polygon");
+    assert_eq!(polygon.name(), "square");
+
+    let circle = Circle { radius: 1 };
+    let shape: &dyn Shape = &circle;
+
+    let result: Result<&dyn Polygon, _> = shape.down_cast();
+    assert_eq!(result.err().map(|(s, e)| (s.sides(), e)), Some((0, CastError::Mismatch)));
+
+    let result: Result<&dyn Polygon, _> = shape.cast();
+    assert_eq!(result.err().map(|(_, e)| e), Some(CastError::Mismatch));
+}
+
+#[test]
+fn mutable_references() {
+    let mut square = Square { side: 2 };
+
+    {
+        let shape: &mut dyn Shape = &mut square;
+        let polygon: &mut dyn Polygon = shape.down_cast().ok().expect("down_cast to Polygon");
+        polygon.grow();
+
+        let shape: &mut dyn Shape = polygon.up_cast();
+        assert_eq!(shape.sides(), 4);
+    }
+
+    assert_eq!(square.side, 3);
+}
+
+#[test]
+fn v_table_of() {
+    let square = Square { side: 2 };
+    let shape: &dyn Shape = &square;
+
+    let v_table = poly::v_table_of(shape);
+    assert_eq!(v_table.struct_info().struct_id(), poly::struct_id::<Square>());
+    assert_eq!(v_table.trait_info().trait_id(), poly::trait_id::<dyn Shape>());
+
+    //  Unregistered structs are reported, not guessed at.
+    let stranger: &dyn Shape = &Stranger;
+    assert!(poly::try_v_table_of(stranger).is_err());
+
+    let result: Result<&dyn Polygon, _> = stranger.down_cast();
+    assert!(result.is_err());
+}
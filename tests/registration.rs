@@ -0,0 +1,87 @@
+//
+//  Types may be registered after start-up, as a plugin would.
+//
+use poly::{poly_trait, PolyStruct, RawClone};
+use poly::{BoxDyn, Class, RegistryError, Registration};
+use poly::{ExtendTrait, StructId, StructInfo, TraitId, TraitInfo};
+use poly::__private::{self, VTableRegistryIndices, VTableRegistryTables};
+
+#[poly_trait]
+trait Plugin: RawClone {
+    fn name(&self) -> String;
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Echo {
+    name: String,
+}
+
+impl Plugin for Echo {
+    fn name(&self) -> String { self.name.clone() }
+}
+
+//  What `poly_hierarchy!` would generate, minus the link-time submission.
+unsafe impl ExtendTrait<dyn Plugin> for Echo {}
+
+fn register_struct_info(collector: &mut Vec<(StructId, StructInfo)>) {
+    collector.push(__private::make_struct_info::<Echo>(<Echo as poly::PolyStruct>::offsets));
+}
+
+//  Plugin itself is registered at link-time, by #[poly_trait].
+fn register_no_trait_info(_: &mut Vec<(TraitId, TraitInfo)>) {}
+
+fn register_vtables(tables: &mut VTableRegistryTables, indices: &mut VTableRegistryIndices) {
+    <dyn Plugin>::__poly_register_v_tables::<Echo>(tables, indices);
+}
+
+static REGISTRATION: Registration =
+    Registration::new(register_struct_info, register_no_trait_info, register_vtables);
+
+//  A new struct, alongside a trait already registered.
+#[derive(PolyStruct)]
+struct Mirror;
+
+fn register_mirror_info(collector: &mut Vec<(StructId, StructInfo)>) {
+    collector.push(__private::make_struct_info::<Mirror>(<Mirror as poly::PolyStruct>::offsets));
+}
+
+fn register_plugin_info(collector: &mut Vec<(TraitId, TraitInfo)>) {
+    collector.push(__private::make_trait_info::<dyn Plugin>());
+}
+
+fn register_no_vtables(_: &mut VTableRegistryTables, _: &mut VTableRegistryIndices) {}
+
+static CONFLICTING: Registration =
+    Registration::new(register_mirror_info, register_plugin_info, register_no_vtables);
+
+#[test]
+fn late_registration() {
+    let echo = poly::struct_id::<Echo>();
+
+    //  Builds the registries, without Echo.
+    assert_eq!(poly::try_struct_info::<Echo>().err(), Some(RegistryError::UnknownStruct(echo)));
+    assert!(poly::try_v_table::<dyn Plugin, Echo>().is_err());
+
+    poly::register(&REGISTRATION).expect("first registration");
+
+    assert!(poly::try_struct_info::<Echo>().is_ok());
+    assert!(poly::try_trait_info::<dyn Plugin>().is_ok());
+    assert!(poly::try_v_table::<dyn Plugin, Echo>().is_ok());
+
+    let plugin: BoxDyn<dyn Plugin, Echo> =
+        BoxDyn::new(Class::new(Echo { name: "echo".to_string() }));
+
+    assert_eq!(plugin.as_trait().name(), "echo");
+    assert_eq!(plugin.clone().as_struct().name, "echo");
+
+    //  Registering twice is an error, and leaves the registries untouched.
+    assert_eq!(poly::register(&REGISTRATION), Err(RegistryError::DuplicateStruct(echo)));
+    assert!(poly::try_struct_info::<Echo>().is_ok());
+
+    //  A registration is all or nothing: Mirror is not registered, as Plugin already is.
+    let plugin = poly::trait_id::<dyn Plugin>();
+
+    assert_eq!(poly::register(&CONFLICTING), Err(RegistryError::DuplicateTrait(plugin)));
+    assert!(poly::try_struct_info::<Mirror>().is_err());
+}
@@ -0,0 +1,145 @@
+//
+//  `()` is the common ancestor of all structs: a BoxDyn<T, ()> may hold any of them.
+//
+use std::cell::Cell;
+
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{BoxDyn, Cast, Class, DynClass, DynRef, DownCast, DownCastRef, UpCast};
+
+//  Tests run concurrently, each on its own thread.
+thread_local! {
+    static DROPS: Cell<usize> = const { Cell::new(0) };
+}
+
+#[poly_trait]
+trait Node: RawClone {
+    fn name(&self) -> String;
+}
+
+#[poly_trait]
+trait Container: Node {
+    fn len(&self) -> usize;
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct NodeData {
+    id: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Text {
+    #[parent]
+    node: NodeData,
+    text: String,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Element {
+    #[parent]
+    node: NodeData,
+    children: Vec<u32>,
+    guard: Guard,
+}
+
+#[derive(Clone, Debug)]
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) { DROPS.with(|d| d.set(d.get() + 1)); }
+}
+
+impl Node for NodeData { fn name(&self) -> String { format!("node-{}", self.id) } }
+impl Node for Text { fn name(&self) -> String { format!("text-{}", self.text) } }
+impl Node for Element { fn name(&self) -> String { format!("element-{}", self.children.len()) } }
+
+impl Container for Element { fn len(&self) -> usize { self.children.len() } }
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct NodeData impl Node;
+    #[derive(PolyStruct)] struct Text impl Node;
+    #[derive(PolyStruct)] struct Element impl Container;
+}
+
+fn text(id: u32, text: &str) -> BoxDyn<dyn Node, ()> {
+    let text: BoxDyn<dyn Node, Text> =
+        BoxDyn::new(Class::new(Text { node: NodeData { id }, text: text.to_string() }));
+    text.up_cast()
+}
+
+fn element(id: u32, children: Vec<u32>) -> BoxDyn<dyn Node, ()> {
+    let element: BoxDyn<dyn Container, Element> =
+        BoxDyn::new(Class::new(Element { node: NodeData { id }, children, guard: Guard }));
+    element.up_cast()
+}
+
+#[test]
+fn heterogeneous_container() {
+    let nodes = vec!(text(1, "hello"), element(2, vec!(1)), text(3, "world"));
+
+    let names: Vec<_> = nodes.iter().map(|n| n.as_trait().name()).collect();
+    assert_eq!(names, vec!("text-hello", "element-1", "text-world"));
+
+    let mut texts: Vec<BoxDyn<dyn Node, Text>> = Vec::new();
+    let mut elements: Vec<BoxDyn<dyn Node, Element>> = Vec::new();
+
+    for node in nodes {
+        let node = match node.down_cast() {
+        Ok(t) => { texts.push(t); continue; }
+        Err((n, _)) => n,
+        };
+
+        elements.push(node.down_cast().expect("down_cast to Element"));
+    }
+
+    assert_eq!(texts.iter().map(|t| t.as_struct().node.id).collect::<Vec<_>>(), vec!(1, 3));
+    assert_eq!(elements[0].as_struct().children, vec!(1));
+}
+
+#[test]
+fn cross_cast() {
+    let node = element(1, vec!(1, 2, 3));
+
+    let container: BoxDyn<dyn Container, Element> = node.cast().expect("cast to Container");
+    assert_eq!(container.as_trait().len(), 3);
+
+    let node: BoxDyn<dyn Node, ()> = container.up_cast();
+    let data: BoxDyn<dyn Node, NodeData> = node.down_cast().expect("down_cast to NodeData");
+    assert_eq!(data.as_struct().id, 1);
+
+    let node = text(2, "leaf");
+    let result: Result<BoxDyn<dyn Container, Element>, _> = node.cast();
+    assert!(result.is_err());
+}
+
+#[test]
+fn by_reference() {
+    let node = element(7, vec!());
+
+    let element: &DynClass<dyn Node, Element> = node.down_cast_ref().expect("down_cast_ref to Element");
+    assert_eq!(element.as_struct().node.id, 7);
+
+    let r = DynRef::new::<()>(&*node);
+    let data: DynRef<dyn Node, NodeData> = r.down_cast().ok().expect("down_cast to NodeData");
+    assert_eq!(data.as_struct().id, 7);
+    assert_eq!(data.as_trait().name(), "element-0");
+}
+
+#[test]
+fn drop_and_clone() {
+    let before = DROPS.with(Cell::get);
+
+    {
+        let node = element(1, vec!(4, 5));
+        let copy = node.clone();
+
+        assert_eq!(copy.as_trait().name(), "element-2");
+
+        let copy: BoxDyn<dyn Node, Element> = copy.down_cast().expect("down_cast to Element");
+        assert_eq!(copy.as_struct().children, vec!(4, 5));
+    }
+
+    assert_eq!(DROPS.with(Cell::get) - before, 2);
+}
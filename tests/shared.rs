@@ -0,0 +1,128 @@
+//
+//  RcDyn and ArcDyn share one object, each handle having its own view of it.
+//
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{ArcDyn, Cast, CastError, Class, DownCast, RcDyn, UpCast};
+
+#[poly_trait]
+trait Node: RawClone + Send + Sync {
+    fn name(&self) -> String;
+}
+
+#[poly_trait]
+trait Element: Node {
+    fn tag(&self) -> &'static str;
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct NodeData {
+    id: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct ElementData {
+    #[parent]
+    node: NodeData,
+    guard: Guard,
+}
+
+//  Counts the drops, each test using its own counter as tests run concurrently.
+#[derive(Clone, Debug)]
+struct Guard(&'static AtomicUsize);
+
+impl Drop for Guard {
+    fn drop(&mut self) { self.0.fetch_add(1, Ordering::SeqCst); }
+}
+
+impl Node for NodeData { fn name(&self) -> String { format!("#node-{}", self.id) } }
+impl Node for ElementData { fn name(&self) -> String { format!("div-{}", self.node.id) } }
+
+impl Element for ElementData { fn tag(&self) -> &'static str { "div" } }
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct NodeData impl Node;
+    #[derive(PolyStruct)] struct ElementData impl Element;
+}
+
+fn element(id: u32, drops: &'static AtomicUsize) -> Class<dyn Element, ElementData> {
+    Class::new(ElementData { node: NodeData { id }, guard: Guard(drops) })
+}
+
+#[test]
+fn rc_casts() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let element = RcDyn::new(element(1, &DROPS));
+    assert_eq!(element.tag(), "div");
+
+    let node: RcDyn<dyn Node, NodeData> = element.clone().up_cast();
+    assert_eq!(node.as_trait().name(), "div-1");
+    assert_eq!(node.as_struct().id, 1);
+
+    //  Casting a clone leaves the others untouched.
+    assert_eq!(element.as_trait().tag(), "div");
+    assert_eq!(RcDyn::strong_count(&element), 2);
+    assert!(RcDyn::ptr_eq(&element, &node));
+
+    let back: RcDyn<dyn Element, ElementData> = node.down_cast().expect("down_cast to ElementData");
+    assert_eq!(back.as_struct().node.id, 1);
+
+    let node: RcDyn<dyn Node, ()> = back.up_cast();
+    let back: RcDyn<dyn Element, ElementData> = node.cast().expect("cast to ElementData");
+    assert_eq!(back.as_dyn_ref().as_struct().node.id, 1);
+
+    let plain: RcDyn<dyn Node, NodeData> = RcDyn::new(Class::new(NodeData { id: 2 }));
+    let result: Result<RcDyn<dyn Element, ElementData>, _> = plain.down_cast();
+    assert_eq!(result.err().map(|(p, e)| (p.as_struct().id, e)), Some((2, CastError::Mismatch)));
+}
+
+#[test]
+fn rc_weak() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let element = RcDyn::new(element(3, &DROPS));
+    let weak = RcDyn::downgrade(&element);
+    let other = weak.clone();
+
+    assert_eq!(RcDyn::weak_count(&element), 2);
+    assert_eq!(weak.upgrade().map(|e| e.as_struct().node.id), Some(3));
+
+    let node: RcDyn<dyn Node, NodeData> = element.up_cast();
+    drop(node);
+
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    assert!(weak.upgrade().is_none());
+    assert!(other.upgrade().is_none());
+}
+
+#[test]
+fn arc_across_threads() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let element = ArcDyn::new(element(4, &DROPS));
+
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            let element = element.clone();
+
+            scope.spawn(move || {
+                let node: ArcDyn<dyn Node, NodeData> = element.up_cast();
+                assert_eq!(node.as_trait().name(), "div-4");
+
+                let weak = ArcDyn::downgrade(&node);
+                assert!(weak.upgrade().is_some());
+            });
+        }
+    });
+
+    assert_eq!(ArcDyn::strong_count(&element), 1);
+    assert_eq!(ArcDyn::weak_count(&element), 0);
+
+    drop(element);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+}
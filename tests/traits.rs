@@ -0,0 +1,139 @@
+//
+//  Trait extension, as generated by `#[poly_trait]`.
+//
+use poly::{poly_hierarchy, poly_trait, PolyStruct, RawClone};
+use poly::{BoxDyn, Class, PolyTrait, RcDyn, TraitExtendTrait, UpCast, VTable};
+
+//  A diamond: Bottom reaches Top through both Left and Right.
+#[poly_trait]
+trait Top: RawClone {
+    fn top(&self) -> u32;
+}
+
+#[poly_trait]
+trait Left: Top {
+    fn left(&self) -> u32;
+}
+
+#[poly_trait]
+trait Right: Top + std::fmt::Debug {
+    fn right(&self) -> u32;
+}
+
+#[poly_trait]
+trait Bottom: Left + Right {
+    fn bottom(&self) -> u32;
+}
+
+//  Neither Foreign, nor the well-known Debug and Send, take part in the hierarchy.
+trait Foreign {}
+
+#[poly_trait(skip(Foreign))]
+trait Alone: Foreign + std::fmt::Debug + Send {}
+
+#[repr(C)]
+#[derive(Clone, Debug, PolyStruct)]
+struct Thing {
+    value: u32,
+}
+
+impl Top for Thing { fn top(&self) -> u32 { self.value } }
+impl Left for Thing { fn left(&self) -> u32 { self.value + 1 } }
+impl Right for Thing { fn right(&self) -> u32 { self.value + 2 } }
+impl Bottom for Thing { fn bottom(&self) -> u32 { self.value + 3 } }
+
+poly_hierarchy! {
+    #[derive(PolyStruct)] struct Thing impl Bottom;
+}
+
+fn slot<T: ?Sized>() -> isize
+    where dyn Bottom: TraitExtendTrait<T>
+{
+    <dyn Bottom as TraitExtendTrait<T>>::offset() / std::mem::size_of::<VTable>() as isize
+}
+
+#[test]
+fn diamond_block() {
+    let block = <dyn Bottom as PolyTrait>::block();
+
+    //  The blocks of Left and Right, [Left, Top] and [Right, Top], each appear whole.
+    let expected = [
+        poly::trait_id::<dyn Bottom>(),
+        poly::trait_id::<dyn Left>(),
+        poly::trait_id::<dyn Top>(),
+        poly::trait_id::<dyn Right>(),
+        poly::trait_id::<dyn Top>(),
+    ];
+    assert_eq!(block, expected);
+
+    assert_eq!(slot::<dyn Bottom>(), 0);
+    assert_eq!(slot::<dyn Left>(), 1);
+    assert_eq!(slot::<dyn Top>(), 2);
+    assert_eq!(slot::<dyn Right>(), 3);
+
+    assert_eq!(<dyn Right as TraitExtendTrait<dyn Top>>::offset(), std::mem::size_of::<VTable>() as isize);
+}
+
+#[test]
+fn registered_block() {
+    //  The v-tables of Thing are laid out as the block of Bottom, each trait being looked up
+    //  at its first slot.
+    let block = <dyn Bottom as PolyTrait>::block();
+    let head = poly::v_table::<dyn Bottom, Thing>() as *const VTable;
+
+    for (slot, trait_id) in block.iter().enumerate() {
+        let v_table = unsafe { &*head.add(slot) };
+        assert_eq!(v_table.trait_info().trait_id(), *trait_id);
+
+        let first = block.iter().position(|id| id == trait_id).expect("a slot");
+        let registered = poly::v_table_by_id(*trait_id, poly::struct_id::<Thing>()).expect("registered v-table");
+        assert!(std::ptr::eq(registered, head.wrapping_add(first)));
+    }
+}
+
+#[test]
+fn skipped_super_traits() {
+    assert_eq!(<dyn Alone as PolyTrait>::block(), [poly::trait_id::<dyn Alone>()]);
+}
+
+#[test]
+fn diamond_cast() {
+    let thing = || -> BoxDyn<dyn Bottom, Thing> { BoxDyn::new(Class::new(Thing { value: 10 })) };
+
+    assert_eq!(thing().as_trait().bottom(), 13);
+
+    let top: BoxDyn<dyn Top, Thing> = thing().up_cast();
+    assert_eq!(top.as_trait().top(), 10);
+
+    let left: BoxDyn<dyn Left, Thing> = thing().up_cast();
+    assert_eq!(left.as_trait().left(), 11);
+
+    let right: BoxDyn<dyn Right, Thing> = thing().up_cast();
+    assert_eq!(right.as_trait().right(), 12);
+}
+
+#[test]
+fn cast_from_second_parent() {
+    //  Right is not first in the block of Bottom, yet its block follows it.
+    let bottom: BoxDyn<dyn Bottom, Thing> = BoxDyn::new(Class::new(Thing { value: 30 }));
+    let right: BoxDyn<dyn Right, Thing> = bottom.up_cast();
+    let top: BoxDyn<dyn Top, Thing> = right.up_cast();
+    assert_eq!(top.as_trait().top(), 30);
+
+    let thing = Thing { value: 40 };
+    let right: &dyn Right = &thing;
+    let top: &dyn Top = right.up_cast();
+    assert_eq!(top.top(), 40);
+
+    let bottom: RcDyn<dyn Bottom, Thing> = RcDyn::new(Class::new(Thing { value: 50 }));
+    let right: RcDyn<dyn Right, Thing> = bottom.up_cast();
+    let top: RcDyn<dyn Top, Thing> = right.up_cast();
+    assert_eq!(top.as_trait().top(), 50);
+
+    //  Only Bottom is listed in the hierarchy, Thing implements Right nonetheless.
+    let right: BoxDyn<dyn Right, Thing> = BoxDyn::new(Class::new(Thing { value: 60 }));
+    assert_eq!(right.as_trait().right(), 62);
+
+    let top: BoxDyn<dyn Top, Thing> = right.up_cast();
+    assert_eq!(top.as_trait().top(), 60);
+}